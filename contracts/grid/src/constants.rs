@@ -6,9 +6,13 @@ pub const ONE_YOCTO: Balance = 1;
 
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 10);
 pub const GAS_FOR_AFTER_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 20);
+pub const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(Gas::ONE_TERA.0 * 50);
 pub const GAS_FOR_CREATE_BOT_AFTER_NEAR: Gas = Gas(Gas::ONE_TERA.0 * 250);
 pub const GAS_FOR_CREATE_BOT_AFTER_ORACLE: Gas = Gas(Gas::ONE_TERA.0 * 200);
 pub const GAS_FOR_AFTER_ORACLE: Gas = Gas(Gas::ONE_TERA.0 * 20);
+// primary price callback, may request the fallback provider
+pub const GAS_FOR_RESOLVE_PAIR_PRICE: Gas = Gas(Gas::ONE_TERA.0 * 50);
+pub const GAS_FOR_TRAIL_BOT_AFTER_ORACLE: Gas = Gas(Gas::ONE_TERA.0 * 200);
 
 /// slippage denominator
 pub const SLIPPAGE_DENOMINATOR: u16 = 10000;
@@ -16,9 +20,20 @@ pub const SLIPPAGE_DENOMINATOR: u16 = 10000;
 /// grid rate denominator
 pub const GRID_RATE_DENOMINATOR: u16 = 10000;
 
+/// trailing stop rate denominator
+pub const TRAILING_STOP_RATE_DENOMINATOR: u16 = 10000;
+
+/// oracle conf rate denominator
+pub const CONF_RATE_DENOMINATOR: u16 = 10000;
+
+/// take price deviation rate denominator
+pub const DEVIATION_RATE_DENOMINATOR: u16 = 10000;
+
 pub const DEFAULT_PROTOCOL_FEE: u128 = 10000;
 pub const DEFAULT_TAKER_FEE: u128 = 500;
 pub const MAX_PROTOCOL_FEE: u128 = 100000;
+/// share of storage refund paid to close_expired_bot caller, at most 50%
+pub const MAX_EXPIRED_CLOSE_REWARD_RATE: u128 = 500000;
 /// protocol fee denominator
 pub const PROTOCOL_FEE_DENOMINATOR: u128 = 1000000;
 
@@ -36,6 +51,9 @@ pub const DEFAULT_TOKEN_STORAGE_FEE: u128 = 1_00_000_000_000_000_000_000_000; //
 
 pub const PAIR_TOKEN_LENGTH: usize = 2;
 
+/// price observations kept for twap
+pub const MAX_PRICE_OBSERVATIONS: usize = 10;
+
 // ms
 pub const DEFAULT_ORACLE_VALID_TIME: u64 = 90000;
 
@@ -43,5 +61,8 @@ pub const PRICE_DENOMINATOR: u128 = 1_000_000_000_000_000_000;
 
 pub const MAX_GRID_COUNT: u16 = 600;
 
+// book orders visited by take_best, including the skipped ones of not takeable bots
+pub const MAX_BOOK_ITERATIONS: u16 = 200;
+
 /// Price per 1 byte of storage from mainnet genesis config.
 pub const STORAGE_PRICE_PER_BYTE: Balance = 10_000_000_000_000_000_000;
@@ -1,4 +1,4 @@
-use near_sdk::{AccountId};
+use near_sdk::{AccountId, Balance};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use std::cmp::{PartialEq, Eq};
@@ -6,7 +6,7 @@ use crate::utils::{U256C};
 use near_sdk::BorshStorageKey;
 use near_sdk::collections::Vector;
 use near_sdk::json_types::U128;
-use crate::oracle::PriceIdentifier;
+use crate::oracle::{Price, PriceIdentifier};
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Eq, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -21,6 +21,55 @@ pub enum GridStatus {
 pub enum GridType {
     EqOffset = 0,
     EqRate = 1,
+    /// per-level amounts supplied by creator
+    Custom = 2,
+}
+
+/// where pair price comes from, the other provider is used as fallback
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum OracleProvider {
+    /// pyth get_price, by base_oracle_id/quote_oracle_id
+    Pyth = 0,
+    /// priceoracle get_price_data, by token account id
+    PriceOracle = 1,
+}
+
+impl Default for OracleProvider {
+    fn default() -> Self {
+        OracleProvider::Pyth
+    }
+}
+
+/// one level of custom grid, price = quote_amount / base_amount
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GridLevel {
+    pub base_amount: U256C,
+    pub quote_amount: U256C,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestGridLevel {
+    pub base_amount: U128,
+    pub quote_amount: U128,
+}
+impl RequestGridLevel {
+    pub fn to_grid_level(&self) -> GridLevel {
+        GridLevel {
+            base_amount: U256C::from(self.base_amount.0),
+            quote_amount: U256C::from(self.quote_amount.0),
+        }
+    }
+}
+
+/// Sell: orders sell base token, Buy: orders buy base token
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum OrderSide {
+    Buy = 0,
+    Sell = 1,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
@@ -31,6 +80,10 @@ pub struct GridBot {
     pub user: AccountId,
     pub bot_id: String,
     pub closed: bool,
+    /// paused by owner, can't be taken
+    pub paused: bool,
+    /// shift the grid one step when price leaves the range, see trail_bot
+    pub trailing: bool,
     pub pair_id: String,
     pub grid_type: GridType,
     pub grid_sell_count: u16,
@@ -52,11 +105,26 @@ pub struct GridBot {
     pub take_profit_price: U256C,
     /// real_stop_loss_price = stop_loss_price / 10^18
     pub stop_loss_price: U256C,
+    /// real_trailing_stop_rate = trailing_stop_rate / 10000, close when price falls this rate below high_water_price, 0 means disable
+    pub trailing_stop_rate: u16,
+    /// the highest oracle price since trailing stop enabled, real_high_water_price = high_water_price / 10^18
+    pub high_water_price: U256C,
     pub valid_until_time: U256C,
     pub total_quote_amount: U256C,
     pub total_base_amount: U256C,
     pub revenue: U256C,
     pub total_revenue: U256C,
+    /// NEAR paid to keeper for each successful trigger_bot/auto_close_bot
+    pub keeper_reward: U256C,
+    /// prepaid NEAR left for keeper reward, refund to user when bot closed
+    pub keeper_reward_balance: U256C,
+    /// only for GridType::Custom, buy levels first, price rise with level
+    #[serde(default)]
+    pub custom_levels: Vec<GridLevel>,
+    /// bytes charged to user's storage account when created with storage balance, released when closed
+    /// 0 means storage fee was attached and the storage is refunded to user when closed
+    #[serde(default)]
+    pub storage_used_bytes: u64,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
@@ -67,6 +135,8 @@ pub struct GridBotOutput {
     pub user: AccountId,
     pub bot_id: String,
     pub closed: bool,
+    pub paused: bool,
+    pub trailing: bool,
     pub pair_id: String,
     pub grid_type: GridType,
     pub grid_sell_count: u16,
@@ -82,11 +152,16 @@ pub struct GridBotOutput {
     pub trigger_price_above_or_below: bool,
     pub take_profit_price: U128,
     pub stop_loss_price: U128,
+    pub trailing_stop_rate: u16,
+    pub high_water_price: U128,
     pub valid_until_time: U128,
     pub total_quote_amount: U128,
     pub total_base_amount: U128,
     pub revenue: U128,
     pub total_revenue: U128,
+    pub keeper_reward: U128,
+    pub keeper_reward_balance: U128,
+    pub storage_used_bytes: u64,
 }
 
 impl Clone for GridBot {
@@ -97,6 +172,8 @@ impl Clone for GridBot {
             user: self.user.clone(),
             bot_id: self.bot_id.clone(),
             closed: self.closed.clone(),
+            paused: self.paused.clone(),
+            trailing: self.trailing.clone(),
             pair_id: self.pair_id.clone(),
             grid_type: self.grid_type.clone(),
             grid_sell_count: self.grid_sell_count.clone(),
@@ -112,11 +189,17 @@ impl Clone for GridBot {
             trigger_price_above_or_below: self.trigger_price_above_or_below.clone(),
             take_profit_price: self.take_profit_price.clone(),
             stop_loss_price: self.stop_loss_price.clone(),
+            trailing_stop_rate: self.trailing_stop_rate.clone(),
+            high_water_price: self.high_water_price.clone(),
             valid_until_time: self.valid_until_time.clone(),
             total_quote_amount: self.total_quote_amount.clone(),
             total_base_amount: self.total_base_amount.clone(),
             revenue: self.revenue.clone(),
             total_revenue: self.total_revenue.clone(),
+            keeper_reward: self.keeper_reward.clone(),
+            keeper_reward_balance: self.keeper_reward_balance.clone(),
+            custom_levels: self.custom_levels.clone(),
+            storage_used_bytes: self.storage_used_bytes.clone(),
         }
     }
 }
@@ -223,6 +306,47 @@ pub struct OrderResult {
     pub order: Order,
     pub flag: bool,
 }
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderOutput {
+    pub order: RequestOrder,
+    /// false means the virtual order, not placed into order_map yet
+    pub placed: bool,
+    pub remaining_sell: U128,
+    pub remaining_buy: U128,
+    /// real_price = price / 10^18, quote amount / base amount
+    pub price: U128,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LevelOrdersOutput {
+    pub level: usize,
+    pub forward_order: OrderOutput,
+    /// None before the forward order taken
+    pub reverse_order: Option<OrderOutput>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepthOutput {
+    /// real_price = price / 10^18
+    pub price: U128,
+    pub base_amount: U128,
+    pub quote_amount: U128,
+    pub order_count: u32,
+}
+
+/// orderbook entry, sorted by price first, quote amount per base amount
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct BookOrderKey {
+    /// real_price = price / 10^18
+    pub price: u128,
+    pub bot_id: String,
+    pub forward_or_reverse: bool,
+    pub level: usize,
+}
+
 impl Clone for OrderKeyInfo {
     fn clone(&self) -> Self {
         OrderKeyInfo {
@@ -247,6 +371,27 @@ pub struct Pair {
     pub base_oracle_id: Option<PriceIdentifier>,
     pub quote_oracle_id: Option<PriceIdentifier>,
     pub require_oracle: bool,
+    /// max oracle conf / price, denominator is CONF_RATE_DENOMINATOR, 0 means not check
+    #[serde(default)]
+    pub max_conf_rate: u16,
+    /// primary oracle provider
+    #[serde(default)]
+    pub oracle_provider: OracleProvider,
+    /// max deviation between take price and cached oracle price, denominator is DEVIATION_RATE_DENOMINATOR, 0 means not check
+    #[serde(default)]
+    pub max_deviation_rate: u16,
+    /// trigger_bot and auto_close_bot use cached twap price if it's fresh
+    #[serde(default)]
+    pub use_cached_price: bool,
+    /// decimals of base token, None until set by owner, required by priceoracle and book price compare
+    #[serde(default)]
+    pub base_token_decimals: Option<u8>,
+    /// decimals of quote token, None until set by owner
+    #[serde(default)]
+    pub quote_token_decimals: Option<u8>,
+    /// reject takes when cached price is expired and max_deviation_rate is set, otherwise the deviation is not checked
+    #[serde(default)]
+    pub halt_on_stale_price: bool,
 }
 
 impl Clone for Pair {
@@ -257,10 +402,112 @@ impl Clone for Pair {
             base_oracle_id: self.base_oracle_id.clone(),
             quote_oracle_id: self.quote_oracle_id.clone(),
             require_oracle: self.require_oracle.clone(),
+            max_conf_rate: self.max_conf_rate.clone(),
+            oracle_provider: self.oracle_provider.clone(),
+            max_deviation_rate: self.max_deviation_rate.clone(),
+            use_cached_price: self.use_cached_price.clone(),
+            base_token_decimals: self.base_token_decimals.clone(),
+            quote_token_decimals: self.quote_token_decimals.clone(),
+            halt_on_stale_price: self.halt_on_stale_price.clone(),
         }
     }
 }
 
+/// NEP-145 storage account of user
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StorageAccount {
+    /// NEAR deposited by storage_deposit
+    pub deposit: Balance,
+    /// bytes charged from deposit
+    pub used_bytes: u64,
+    /// tokens registered with storage balance, unregistered by storage_unregister
+    pub tokens: Vec<AccountId>,
+}
+
+/// latest observations of one price identifier, oldest first
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PriceCache {
+    pub observations: Vec<Price>,
+}
+
+/// failed payout of user, settled by retry_withdrawals
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingWithdrawal {
+    pub token: AccountId,
+    pub amount: U128,
+    pub reason: String,
+    /// ms, time of the latest failure
+    pub update_time: u64,
+}
+
+/// NEP-245 token, balances are kept under its key from internal_get_multi_token_key
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultiToken {
+    pub contract_id: AccountId,
+    pub token_id: String,
+}
+
+/// totals of one token, updated with every balance change
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct TokenAccounting {
+    pub free: U256C,
+    pub locked: U256C,
+    pub protocol_fee: U256C,
+    pub refer_fee: U256C,
+    pub pending_withdrawal: U256C,
+    /// ft_balance_of(current_account) at last reconcile
+    pub reconciled_balance: U256C,
+    /// ms
+    pub reconcile_time: u64,
+}
+
+impl TokenAccounting {
+    pub fn owned(&self) -> U256C {
+        return self.free + self.locked + self.protocol_fee + self.refer_fee + self.pending_withdrawal;
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenAccountingOutput {
+    pub free: U128,
+    pub locked: U128,
+    pub protocol_fee: U128,
+    pub refer_fee: U128,
+    pub pending_withdrawal: U128,
+    /// global balance, owned assets and transfers in flight
+    pub recorded: U128,
+    /// reconciled balance not recorded
+    pub unowned: U128,
+    pub reconciled_balance: U128,
+    /// ms, 0 if never reconciled
+    pub reconcile_time: u64,
+}
+
+/// pair price from price cache, quote amount / base amount * 10^18
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PairPrice {
+    pub price: U128,
+    pub twap_price: U128,
+    /// ms, the earlier publish time of base price and quote price
+    pub update_time: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PairOutput {
+    pub pair_id: String,
+    pub pair: Pair,
+    pub base_min_deposit: U128,
+    pub quote_min_deposit: U128,
+    /// not closed bots count
+    pub bot_count: u64,
+    pub paused: bool,
+}
+
 #[derive(BorshStorageKey, BorshSerialize)]
 pub enum StorageKey {
     UserBalanceMainKey,
@@ -271,6 +518,22 @@ pub enum StorageKey {
     OrdersSubKey(u64),
     ReferFeeMainKey,
     ReferFeeSubKey(AccountId),
+    OrderbookMainKey,
+    OrderbookSubKey(String),
+    UserBotsMainKey,
+    UserBotsSubKey(AccountId),
+    UserOpenBotsMainKey,
+    UserOpenBotsSubKey(AccountId),
+    PairBotsMainKey,
+    PairBotsSubKey(String),
+    PairIds,
+    PriceCacheMainKey,
+    StorageAccountMainKey,
+    PendingWithdrawalMainKey,
+    TokenAccountingMainKey,
+    MultiTokenMainKey,
+    Bots,
+    Pairs,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
@@ -280,3 +543,32 @@ pub struct TakeRequest {
     pub maker_orders: Vec<OrderKeyInfo>,
     pub return_near: Option<bool>,
 }
+
+/// same params as create_bot, used as ft_transfer_call msg
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CreateBotRequest {
+    pub name: String,
+    pub pair_id: String,
+    pub slippage: u16,
+    pub grid_type: GridType,
+    pub grid_rate: u16,
+    pub grid_offset: U128,
+    pub first_base_amount: U128,
+    pub first_quote_amount: U128,
+    pub last_base_amount: U128,
+    pub last_quote_amount: U128,
+    pub fill_base_or_quote: bool,
+    pub grid_sell_count: u16,
+    pub grid_buy_count: u16,
+    pub trigger_price: U128,
+    pub take_profit_price: U128,
+    pub stop_loss_price: U128,
+    pub valid_until_time: U128,
+    pub entry_price: U128,
+    pub recommender: Option<AccountId>,
+    pub custom_levels: Option<Vec<RequestGridLevel>>,
+    pub keeper_reward: Option<U128>,
+    /// prepaid NEAR budget of keeper reward, attached together with storage fee
+    pub keeper_reward_balance: Option<U128>,
+}
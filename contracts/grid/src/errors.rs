@@ -26,6 +26,7 @@ pub const INVALID_REVENUE: &str = "INVALID_REVENUE";
 pub const INVALID_BOT_ID: &str = "INVALID_BOT_ID";
 pub const BOT_CLOSED: &str = "BOT_CLOSED";
 pub const BOT_DISABLE: &str = "BOT_DISABLE";
+pub const BOT_PAUSED: &str = "BOT_PAUSED";
 pub const INVALID_PARAM: &str = "INVALID_PARAM";
 pub const INVALID_ORACLE_PARAM: &str = "INVALID_ORACLE_PARAM";
 pub const ERR_NOT_ALLOWED: &str = "ERR_NOT_ALLOWED";
@@ -33,6 +34,7 @@ pub const INVALID_TRANSFER_DATA: &str = "INVALID_TRANSFER_DATA";
 pub const INVALID_BALANCE: &str = "INVALID_BALANCE";
 pub const PAIR_EXIST: &str = "PAIR_EXIST";
 pub const BOT_EXPIRED: &str = "BOT_EXPIRED";
+pub const BOT_NOT_EXPIRED: &str = "BOT_NOT_EXPIRED";
 pub const INVALID_PROTOCOL_FEE: &str = "INVALID_PROTOCOL_FEE";
 pub const INVALID_FORWARD_OR_REVERSE: &str = "INVALID_FORWARD_OR_REVERSE";
 pub const INVALID_AMOUNT: &str = "INVALID_AMOUNT";
@@ -48,6 +50,8 @@ pub const INVALID_PAIR_PRICE_LENGTH: &str = "INVALID_PAIR_PRICE_LENGTH";
 pub const INVALID_BASE_AMOUNT: &str = "INVALID_BASE_AMOUNT";
 pub const INVALID_QUOTE_AMOUNT: &str = "INVALID_QUOTE_AMOUNT";
 pub const CAN_NOT_TRIGGER: &str = "CAN_NOT_TRIGGER";
+pub const CAN_NOT_TRAIL: &str = "CAN_NOT_TRAIL";
+pub const INVALID_TRAILING_STOP_RATE: &str = "INVALID_TRAILING_STOP_RATE";
 pub const BASE_TOO_SMALL: &str = "BASE_TOO_SMALL";
 pub const QUOTE_TOO_SMALL: &str = "QUOTE_TOO_SMALL";
 pub const WRAP_TO_WNEAR_ERROR: &str = "WRAP_TO_WNEAR_ERROR";
@@ -58,9 +62,23 @@ pub const INVALID_STATUS: &str = "INVALID_STATUS";
 pub const MORE_THAN_MAX_GRID_COUNT: &str = "MORE_THAN_MAX_GRID_COUNT";
 pub const INVALID_BOT_STATUS: &str = "INVALID_BOT_STATUS";
 pub const INVALID_UNTIL_TIME: &str = "INVALID_UNTIL_TIME";
+pub const INVALID_CUSTOM_LEVELS: &str = "INVALID_CUSTOM_LEVELS";
 pub const LESS_GAS: &str = "LESS_GAS";
 pub const INVALID_PAIR: &str = "INVALID_PAIR";
 pub const WNEAR_NOT_REGISTERED: &str = "WNEAR_NOT_REGISTERED";
-
-
-
+pub const INVALID_CONF_RATE: &str = "INVALID_CONF_RATE";
+pub const INVALID_DEVIATION_RATE: &str = "INVALID_DEVIATION_RATE";
+pub const PAIR_PRICE_EXPIRED: &str = "PAIR_PRICE_EXPIRED";
+pub const PRICE_DEVIATION_TOO_LARGE: &str = "PRICE_DEVIATION_TOO_LARGE";
+pub const STORAGE_NOT_REGISTERED: &str = "STORAGE_NOT_REGISTERED";
+pub const LESS_STORAGE_BALANCE: &str = "LESS_STORAGE_BALANCE";
+pub const CAN_NOT_UNREGISTER: &str = "CAN_NOT_UNREGISTER";
+pub const FT_TRANSFER_FAILED: &str = "FT_TRANSFER_FAILED";
+pub const NEAR_WITHDRAW_FAILED: &str = "NEAR_WITHDRAW_FAILED";
+pub const NO_PENDING_WITHDRAWALS: &str = "NO_PENDING_WITHDRAWALS";
+pub const RECONCILE_ERROR: &str = "RECONCILE_ERROR";
+pub const MULTI_TOKEN_EXIST: &str = "MULTI_TOKEN_EXIST";
+pub const DEPOSIT_NEAR_REJECTED: &str = "DEPOSIT_NEAR_REJECTED";
+pub const TOKEN_DECIMALS_NOT_SET: &str = "TOKEN_DECIMALS_NOT_SET";
+pub const INVALID_KEEPER_REWARD: &str = "INVALID_KEEPER_REWARD";
+pub const INVALID_EXPIRED_CLOSE_REWARD_RATE: &str = "INVALID_EXPIRED_CLOSE_REWARD_RATE";
@@ -16,6 +16,46 @@ pub mod emit {
         pub token_id: &'a AccountId,
     }
 
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct WithdrawTo<'a> {
+        pub account_id: &'a AccountId,
+        pub receiver_id: &'a AccountId,
+        #[serde(with = "u128_dec_format")]
+        pub amount: Balance,
+        pub token_id: &'a AccountId,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct PendingWithdrawalAdded<'a> {
+        pub account_id: &'a AccountId,
+        #[serde(with = "u128_dec_format")]
+        pub amount: Balance,
+        pub token_id: &'a AccountId,
+        pub reason: &'a str,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct TokenDiscrepancy<'a> {
+        pub token_id: &'a AccountId,
+        #[serde(with = "u128_dec_format")]
+        pub balance: Balance,
+        #[serde(with = "u128_dec_format")]
+        pub recorded: Balance,
+        #[serde(with = "u128_dec_format")]
+        pub owned: Balance,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct AccountAmount<'a> {
+        pub account_id: &'a AccountId,
+        #[serde(with = "u128_dec_format")]
+        pub amount: Balance,
+    }
+
     #[derive(Serialize)]
     #[serde(crate = "near_sdk::serde")]
     struct CreateBot<'a> {
@@ -31,6 +71,25 @@ pub mod emit {
         pub grid_bot: GridBotOutput,
     }
 
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct UpdateBot<'a> {
+        pub account_id: &'a AccountId,
+        pub bot_id: String,
+        pub grid_bot: GridBotOutput,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct ChangeBotAssets<'a> {
+        pub account_id: &'a AccountId,
+        pub bot_id: String,
+        pub increase: bool,
+        pub base_amount: U128,
+        pub quote_amount: U128,
+        pub grid_bot: GridBotOutput,
+    }
+
     #[derive(Serialize)]
     #[serde(crate = "near_sdk::serde")]
     struct CreateBotError<'a> {
@@ -109,6 +168,54 @@ pub mod emit {
         pub revenue: Balance,
     }
 
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct PauseOrResumeBot<'a> {
+        pub account_id: &'a AccountId,
+        pub bot_id: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct UpdateHighWaterPrice {
+        pub bot_id: String,
+        pub high_water_price: U128,
+        pub base_price: String,
+        pub quote_price: String,
+        pub base_expo: String,
+        pub quote_expo: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct RefreshPrices {
+        pub pair_id: String,
+        pub base_price: String,
+        pub quote_price: String,
+        pub base_expo: String,
+        pub quote_expo: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct TrailBot {
+        pub bot_id: String,
+        pub up: bool,
+        pub base_price: String,
+        pub quote_price: String,
+        pub base_expo: String,
+        pub quote_expo: String,
+    }
+
+    #[derive(Serialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct KeeperReward<'a> {
+        pub keeper: &'a AccountId,
+        pub bot_id: String,
+        #[serde(with = "u128_dec_format")]
+        pub reward: Balance,
+    }
+
     #[derive(Serialize)]
     #[serde(crate = "near_sdk::serde")]
     struct TriggerBot {
@@ -159,6 +266,65 @@ pub mod emit {
         );
     }
 
+    pub fn pending_withdrawal_added(account_id: &AccountId, amount: Balance, token_id: &AccountId, reason: &str) {
+        log_event(
+            "pending_withdrawal_added",
+            PendingWithdrawalAdded {
+                account_id,
+                amount,
+                token_id,
+                reason,
+            },
+        );
+    }
+
+    pub fn pending_withdrawal_claimed(account_id: &AccountId, amount: Balance, token_id: &AccountId) {
+        log_event(
+            "pending_withdrawal_claimed",
+            AccountAmountToken {
+                account_id: &account_id,
+                amount,
+                token_id: &token_id,
+            },
+        );
+    }
+
+    pub fn token_discrepancy(token_id: &AccountId, balance: Balance, recorded: Balance, owned: Balance) {
+        log_event(
+            "token_discrepancy",
+            TokenDiscrepancy {
+                token_id,
+                balance,
+                recorded,
+                owned,
+            },
+        );
+    }
+
+    pub fn withdraw_to_started(account_id: &AccountId, receiver_id: &AccountId, amount: Balance, token_id: &AccountId) {
+        log_event(
+            "withdraw_to_started",
+            WithdrawTo {
+                account_id,
+                receiver_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn withdraw_to_succeeded(account_id: &AccountId, receiver_id: &AccountId, amount: Balance, token_id: &AccountId) {
+        log_event(
+            "withdraw_to_succeeded",
+            WithdrawTo {
+                account_id,
+                receiver_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
     pub fn withdraw_succeeded(account_id: &AccountId, amount: Balance, token_id: &AccountId) {
         log_event(
             "withdraw_succeeded",
@@ -288,6 +454,36 @@ pub mod emit {
         );
     }
 
+    pub fn storage_deposit(account_id: &AccountId, amount: Balance) {
+        log_event(
+            "storage_deposit",
+            AccountAmount {
+                account_id,
+                amount,
+            },
+        );
+    }
+
+    pub fn storage_withdraw(account_id: &AccountId, amount: Balance) {
+        log_event(
+            "storage_withdraw",
+            AccountAmount {
+                account_id,
+                amount,
+            },
+        );
+    }
+
+    pub fn storage_unregister(account_id: &AccountId, amount: Balance) {
+        log_event(
+            "storage_unregister",
+            AccountAmount {
+                account_id,
+                amount,
+            },
+        );
+    }
+
     pub fn storage_deposit_failed(account_id: &AccountId, amount: Balance, token_id: &AccountId) {
         log_event(
             "storage_deposit_failed",
@@ -370,6 +566,31 @@ pub mod emit {
         );
     }
 
+    pub fn update_bot(account_id: &AccountId, bot_id: String, grid_bot: GridBotOutput) {
+        log_event(
+            "update_bot",
+            UpdateBot {
+                account_id: &account_id,
+                bot_id,
+                grid_bot
+            },
+        );
+    }
+
+    pub fn change_bot_assets(account_id: &AccountId, bot_id: String, increase: bool, base_amount: U256C, quote_amount: U256C, grid_bot: GridBotOutput) {
+        log_event(
+            "change_bot_assets",
+            ChangeBotAssets {
+                account_id: &account_id,
+                bot_id,
+                increase,
+                base_amount: U128::from(base_amount.as_u128()),
+                quote_amount: U128::from(quote_amount.as_u128()),
+                grid_bot
+            },
+        );
+    }
+
     pub fn close_bot(account_id: &AccountId, bot_id: String, refund: Balance) {
         log_event(
             "close_bot",
@@ -406,6 +627,78 @@ pub mod emit {
         );
     }
 
+    pub fn pause_bot(account_id: &AccountId, bot_id: String) {
+        log_event(
+            "pause_bot",
+            PauseOrResumeBot {
+                account_id: &account_id,
+                bot_id,
+            },
+        );
+    }
+
+    pub fn resume_bot(account_id: &AccountId, bot_id: String) {
+        log_event(
+            "resume_bot",
+            PauseOrResumeBot {
+                account_id: &account_id,
+                bot_id,
+            },
+        );
+    }
+
+    pub fn update_high_water_price(bot_id: String, high_water_price: U128, base_price: String, quote_price: String, base_expo: String, quote_expo: String) {
+        log_event(
+            "update_high_water_price",
+            UpdateHighWaterPrice {
+                bot_id,
+                high_water_price,
+                base_price,
+                quote_price,
+                base_expo,
+                quote_expo,
+            },
+        );
+    }
+
+    pub fn refresh_prices(pair_id: String, base_price: String, quote_price: String, base_expo: String, quote_expo: String) {
+        log_event(
+            "refresh_prices",
+            RefreshPrices {
+                pair_id,
+                base_price,
+                quote_price,
+                base_expo,
+                quote_expo,
+            },
+        );
+    }
+
+    pub fn trail_bot(bot_id: String, up: bool, base_price: String, quote_price: String, base_expo: String, quote_expo: String) {
+        log_event(
+            "trail_bot",
+            TrailBot {
+                bot_id,
+                up,
+                base_price,
+                quote_price,
+                base_expo,
+                quote_expo,
+            },
+        );
+    }
+
+    pub fn keeper_reward(keeper: &AccountId, bot_id: String, reward: Balance) {
+        log_event(
+            "keeper_reward",
+            KeeperReward {
+                keeper: &keeper,
+                bot_id,
+                reward,
+            },
+        );
+    }
+
     pub fn trigger_bot(bot_id: String, base_price: String, quote_price: String, base_expo: String, quote_expo: String) {
         log_event(
             "trigger_bot",
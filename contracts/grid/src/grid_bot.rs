@@ -1,7 +1,6 @@
 use crate::*;
-use near_sdk::{assert_one_yocto, Gas, near_bindgen, Promise, require};
+use near_sdk::{assert_one_yocto, near_bindgen, Promise, require};
 use near_sdk::json_types::U128;
-use serde_json::json;
 use crate::entity::{GridType};
 use crate::events::emit;
 use crate::GridStatus::Shutdown;
@@ -14,80 +13,52 @@ impl GridBotContract {
                       grid_rate: u16, grid_offset: U128, first_base_amount: U128, first_quote_amount: U128,
                       last_base_amount: U128, last_quote_amount: U128, fill_base_or_quote: bool, grid_sell_count: u16, grid_buy_count: u16,
                       trigger_price: U128, take_profit_price: U128, stop_loss_price: U128, valid_until_time: U128,
-                      entry_price: U128, recommender: Option<AccountId>) {
+                      entry_price: U128, recommender: Option<AccountId>, custom_levels: Option<Vec<RequestGridLevel>>,
+                      keeper_reward: Option<U128>, keeper_reward_balance: Option<U128>) {
         let user = env::predecessor_account_id();
-        require!(self.global_balances_map.contains_key(&self.wnear), INVALID_PAIR);
-        let grid_offset_256 = U256C::from(grid_offset.0);
-        let first_base_amount_256 = U256C::from(first_base_amount.0);
-        let first_quote_amount_256 = U256C::from(first_quote_amount.0);
-        let last_base_amount_256 = U256C::from(last_base_amount.0);
-        let last_quote_amount_256 = U256C::from(last_quote_amount.0);
-        let trigger_price_256 = U256C::from(trigger_price.0);
-        let take_profit_price_256 = U256C::from(take_profit_price.0);
-        let stop_loss_price_256 = U256C::from(stop_loss_price.0);
-        let valid_until_time_256 = U256C::from(valid_until_time.0);
+        let request = CreateBotRequest {name, pair_id, slippage, grid_type, grid_rate, grid_offset, first_base_amount, first_quote_amount,
+            last_base_amount, last_quote_amount, fill_base_or_quote, grid_sell_count, grid_buy_count, trigger_price, take_profit_price,
+            stop_loss_price, valid_until_time, entry_price, recommender: recommender.clone(), custom_levels, keeper_reward, keeper_reward_balance};
         let entry_price_256 = U256C::from(entry_price.0);
-
-        require!(valid_until_time.0 > env::block_timestamp_ms() as u128, INVALID_UNTIL_TIME);
-
-        require!(self.pair_map.contains_key(&pair_id), INVALID_PAIR_ID);
-        let pair = self.pair_map.get(&pair_id).unwrap().clone();
-        // if pair not support oracle, will can't use trigger_price/take_profit_price/stop_loss_price
-        require!(pair.require_oracle || !pair.require_oracle && trigger_price.0 == 0 && take_profit_price.0 == 0 && stop_loss_price.0 == 0, INVALID_ORACLE_PARAM);
-
-        // require!(self.status == GridStatus::Running, PAUSE_OR_SHUTDOWN);
-        if self.status != GridStatus::Running {
-            self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), PAUSE_OR_SHUTDOWN);
-            return;
-        }
-
-        if grid_buy_count + grid_sell_count > MAX_GRID_COUNT {
-            self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), MORE_THAN_MAX_GRID_COUNT);
-            return;
-        }
-
-        // calculate all assets
-        let (base_amount_sell, quote_amount_buy) = GridBotContract::internal_calculate_bot_assets(first_quote_amount_256.clone(), last_base_amount_256.clone(), grid_sell_count.clone(), grid_buy_count.clone(),
-                                                       grid_type.clone(), grid_rate.clone(), grid_offset_256.clone(), fill_base_or_quote.clone());
-
-        // last_quote_amount / last_base_amount > first_quote_amount > first_base_amount
-        // amount must u128, u128 * u128 <= u256, so, it's ok
-        let (result, reason) = self.internal_check_bot_amount(grid_sell_count, grid_buy_count, first_base_amount_256, first_quote_amount_256,
-                                                            last_base_amount_256, last_quote_amount_256, &pair, base_amount_sell, quote_amount_buy);
-        if !result {
-            self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), &reason);
-            return;
-        }
-
-        // create bot
-        let mut new_grid_bot = GridBot {name, active: false, user: user.clone(), bot_id: "".to_string(), closed: false, pair_id, grid_type,
-            grid_sell_count: grid_sell_count.clone(), grid_buy_count: grid_buy_count.clone(), grid_rate, grid_offset: grid_offset_256,
-            first_base_amount: first_base_amount_256, first_quote_amount: first_quote_amount_256, last_base_amount: last_base_amount_256,
-            last_quote_amount: last_quote_amount_256, fill_base_or_quote, trigger_price: trigger_price_256, trigger_price_above_or_below: false,
-            take_profit_price: take_profit_price_256, stop_loss_price: stop_loss_price_256, valid_until_time: valid_until_time_256,
-            total_quote_amount: quote_amount_buy, total_base_amount: base_amount_sell, revenue: U256C::from(0), total_revenue: U256C::from(0)
+        let pair = self.internal_get_create_bot_pair(&request);
+        let (mut new_grid_bot, storage_fee) = match self.internal_build_grid_bot(&user, &pair, request) {
+            Ok(result) => result,
+            Err(reason) => {
+                self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), &reason);
+                return;
+            }
         };
+        let base_amount_sell = new_grid_bot.total_base_amount;
+        let quote_amount_buy = new_grid_bot.total_quote_amount;
 
+        let attached_deposit = env::attached_deposit();
         if self.internal_need_wrap_near(&user, &pair, base_amount_sell, quote_amount_buy) {
-            // wrap near to wnear first
+            // wrap near to wnear first, bot's NEAR must be attached
             let bot_near_amount = self.internal_get_bot_near_amount(&new_grid_bot, &pair);
-            // check storage fee
-            if !self.query_user_token_registered(user.clone(), self.wnear.clone()) || env::attached_deposit() - bot_near_amount < self.base_create_storage_fee + self.per_grid_storage_fee * (grid_buy_count + grid_sell_count) as u128 {
-                self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), LESS_STORAGE_FEE);
+            if !self.query_user_token_registered(user.clone(), self.wnear.clone()) || attached_deposit < bot_near_amount {
+                self.internal_create_bot_refund_with_near(&user, &pair, attached_deposit, LESS_STORAGE_FEE);
+                return;
+            }
+            // storage fee can be prepaid by storage_deposit
+            let storage_drawn_op = self.internal_draw_storage_balance(&user, attached_deposit - bot_near_amount, storage_fee);
+            if storage_drawn_op.is_none() {
+                self.internal_create_bot_refund_with_near(&user, &pair, attached_deposit, LESS_STORAGE_FEE);
                 return;
             }
-            self.deposit_near_to_get_wnear_for_create_bot(&pair, &user, slippage, &entry_price_256, &mut new_grid_bot, bot_near_amount, recommender, env::attached_deposit() - bot_near_amount);
+            self.deposit_near_to_get_wnear_for_create_bot(&pair, &user, slippage, &entry_price_256, &mut new_grid_bot, bot_near_amount, recommender, attached_deposit - bot_near_amount, storage_drawn_op.unwrap());
         } else {
-            // check storage fee
-            if env::attached_deposit() < self.base_create_storage_fee + self.per_grid_storage_fee * (grid_buy_count + grid_sell_count) as u128 {
-                self.internal_create_bot_refund_with_near(&user, &pair, env::attached_deposit(), LESS_STORAGE_FEE);
+            // storage fee can be prepaid by storage_deposit
+            let storage_drawn_op = self.internal_draw_storage_balance(&user, attached_deposit, storage_fee);
+            if storage_drawn_op.is_none() {
+                self.internal_create_bot_refund_with_near(&user, &pair, attached_deposit, LESS_STORAGE_FEE);
                 return;
             }
+            let storage_drawn = storage_drawn_op.unwrap();
             // request token price
             if pair.require_oracle {
-                self.get_price_for_create_bot(&pair, &user, slippage, &entry_price_256, &mut new_grid_bot, recommender, env::attached_deposit());
+                self.get_price_for_create_bot(&pair, &user, slippage, &entry_price_256, &mut new_grid_bot, recommender, attached_deposit, storage_drawn);
             } else {
-                self.internal_create_bot(None, None, &user, slippage, &entry_price_256, &pair, recommender, env::attached_deposit(), &mut new_grid_bot);
+                self.internal_create_bot(None, None, &user, slippage, &entry_price_256, &pair, recommender, attached_deposit, storage_drawn, &mut new_grid_bot);
             }
         }
     }
@@ -100,6 +71,18 @@ impl GridBotContract {
         self.internal_take_orders(&(env::predecessor_account_id()), &take_order.to_order(), maker_orders);
     }
 
+    /// take the best price orders of pair, at most max_makers maker orders
+    #[payable]
+    pub fn take_best(&mut self, pair_id: String, take_order: RequestOrder, max_makers: u16) {
+        assert_one_yocto();
+        require!(self.market_user_map.contains_key(&(env::predecessor_account_id())), INVALID_USER);
+        require!(self.pair_map.contains_key(&pair_id), INVALID_PAIR_ID);
+        require!(take_order.amount_sell.0 >= self.deposit_limit_map.get(&take_order.token_sell).unwrap().as_u128(), INVALID_AMOUNT);
+        let taker_order = take_order.to_order();
+        let maker_orders = self.internal_get_best_maker_orders(&pair_id, &taker_order, max_makers);
+        self.internal_take_orders(&(env::predecessor_account_id()), &taker_order, maker_orders);
+    }
+
     #[payable]
     pub fn close_bot(&mut self, bot_id: String) {
         assert_one_yocto();
@@ -110,7 +93,126 @@ impl GridBotContract {
         // check permission, user self close
         require!(env::predecessor_account_id() == bot.user, INVALID_USER);
 
-        self.internal_close_bot(&env::predecessor_account_id(), &bot_id, &mut bot, &pair);
+        self.internal_close_bot(&env::predecessor_account_id(), &bot_id, &mut bot, &pair, 0);
+    }
+
+    /// anyone can close an expired bot, assets go back to bot's owner, caller get a share of storage refund
+    pub fn close_expired_bot(&mut self, bot_id: String) {
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(bot.valid_until_time < U256C::from(env::block_timestamp_ms()), BOT_NOT_EXPIRED);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+
+        self.internal_close_bot(&env::predecessor_account_id(), &bot_id, &mut bot, &pair, self.expired_close_reward_rate);
+    }
+
+    /// only bot's owner, None means not change
+    /// trigger price can only be changed before bot triggered, and keep the trigger direction
+    #[payable]
+    pub fn update_bot(&mut self, bot_id: String, name: Option<String>, trigger_price: Option<U128>, take_profit_price: Option<U128>,
+                      stop_loss_price: Option<U128>, valid_until_time: Option<U128>, trailing: Option<bool>, trailing_stop_rate: Option<u16>) {
+        assert_one_yocto();
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(env::predecessor_account_id() == bot.user, INVALID_USER);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+
+        if let Some(new_valid_until_time) = valid_until_time {
+            require!(new_valid_until_time.0 > env::block_timestamp_ms() as u128, INVALID_UNTIL_TIME);
+            bot.valid_until_time = U256C::from(new_valid_until_time.0);
+        }
+        if let Some(new_trigger_price) = trigger_price {
+            require!(!bot.active || new_trigger_price.0 == bot.trigger_price.as_u128(), BOT_IS_ACTIVE);
+            bot.trigger_price = U256C::from(new_trigger_price.0);
+            if bot.trigger_price == U256C::from(0) {
+                bot.active = true;
+            }
+        }
+        if let Some(new_take_profit_price) = take_profit_price {
+            bot.take_profit_price = U256C::from(new_take_profit_price.0);
+        }
+        if let Some(new_stop_loss_price) = stop_loss_price {
+            bot.stop_loss_price = U256C::from(new_stop_loss_price.0);
+        }
+        if let Some(new_trailing_stop_rate) = trailing_stop_rate {
+            require!(new_trailing_stop_rate < TRAILING_STOP_RATE_DENOMINATOR, INVALID_TRAILING_STOP_RATE);
+            bot.trailing_stop_rate = new_trailing_stop_rate;
+            // start a new peak
+            bot.high_water_price = U256C::from(0);
+        }
+        require!(GridBotContract::internal_check_oracle_param(&pair, bot.trigger_price.as_u128(), bot.take_profit_price.as_u128(), bot.stop_loss_price.as_u128()), INVALID_ORACLE_PARAM);
+        require!(pair.require_oracle || bot.trailing_stop_rate == 0, INVALID_ORACLE_PARAM);
+        if let Some(new_name) = name {
+            bot.name = new_name;
+        }
+        if let Some(new_trailing) = trailing {
+            // custom grid has no step to move
+            require!(!new_trailing || bot.grid_type != GridType::Custom, CAN_NOT_TRAIL);
+            bot.trailing = new_trailing;
+        }
+
+        self.bot_map.insert(&bot_id, &bot);
+        emit::update_bot(&env::predecessor_account_id(), bot_id, self.internal_get_grid_bot_output(&bot));
+    }
+
+    /// base_amount/quote_amount are the max amounts to add, the bot's grid keeps its shape and is scaled by the lower ratio of them
+    #[payable]
+    pub fn increase_bot_assets(&mut self, bot_id: String, base_amount: U128, quote_amount: U128) {
+        assert_one_yocto();
+        require!(self.status == GridStatus::Running, PAUSE_OR_SHUTDOWN);
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(env::predecessor_account_id() == bot.user, INVALID_USER);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+
+        let (change_base_amount, change_quote_amount) = self.internal_change_bot_assets(&mut bot, &pair, U256C::from(base_amount.0), U256C::from(quote_amount.0), true);
+        emit::change_bot_assets(&env::predecessor_account_id(), bot_id, true, change_base_amount, change_quote_amount, self.internal_get_grid_bot_output(&bot));
+    }
+
+    /// base_amount/quote_amount are the max amounts to remove into user's balance, the bot's grid is scaled down by the lower ratio of them
+    #[payable]
+    pub fn decrease_bot_assets(&mut self, bot_id: String, base_amount: U128, quote_amount: U128) {
+        assert_one_yocto();
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(env::predecessor_account_id() == bot.user, INVALID_USER);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+
+        let (change_base_amount, change_quote_amount) = self.internal_change_bot_assets(&mut bot, &pair, U256C::from(base_amount.0), U256C::from(quote_amount.0), false);
+        emit::change_bot_assets(&env::predecessor_account_id(), bot_id, false, change_base_amount, change_quote_amount, self.internal_get_grid_bot_output(&bot));
+    }
+
+    /// only bot's owner, paused bot can't be taken, assets and orders keep unchanged
+    #[payable]
+    pub fn pause_bot(&mut self, bot_id: String) {
+        assert_one_yocto();
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(!bot.paused, INVALID_BOT_STATUS);
+        require!(env::predecessor_account_id() == bot.user, INVALID_USER);
+
+        bot.paused = true;
+        self.bot_map.insert(&bot_id, &bot);
+        emit::pause_bot(&env::predecessor_account_id(), bot_id);
+    }
+
+    #[payable]
+    pub fn resume_bot(&mut self, bot_id: String) {
+        assert_one_yocto();
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(bot.paused, INVALID_BOT_STATUS);
+        require!(env::predecessor_account_id() == bot.user, INVALID_USER);
+
+        bot.paused = false;
+        self.bot_map.insert(&bot_id, &bot);
+        emit::resume_bot(&env::predecessor_account_id(), bot_id);
     }
 
     pub fn auto_close_bot(&mut self, bot_id: String) {
@@ -120,7 +222,13 @@ impl GridBotContract {
         let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
         require!(pair.require_oracle, INVALID_PAIR);
 
-        self.get_price_for_close_bot(&env::predecessor_account_id(), &pair, &mut bot);
+        if pair.use_cached_price {
+            if let Some((base_price, quote_price)) = self.internal_get_cached_prices(&pair, true) {
+                self.internal_auto_close_bot(base_price, quote_price, &env::predecessor_account_id(), &bot_id, &mut bot, &pair);
+                return;
+            }
+        }
+        self.get_price_for_close_bot(&env::predecessor_account_id(), &pair, bot_id);
     }
 
     #[payable]
@@ -137,13 +245,53 @@ impl GridBotContract {
         emit::claim(&env::predecessor_account_id(), &(bot.user), bot_id, &revenue_token, revenue);
     }
 
+    /// anyone can raise the high water price of a trailing stop bot to current oracle price
+    pub fn update_high_water_price(&mut self, bot_id: String) {
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let bot = self.bot_map.get(&bot_id).unwrap();
+        require!(!bot.closed && bot.trailing_stop_rate > 0, INVALID_BOT_STATUS);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+        require!(pair.require_oracle, INVALID_PAIR);
+        self.get_price_for_high_water_price(&pair, bot_id);
+    }
+
+    /// anyone can refresh the price cache of pairs, used by take price deviation check and cached trigger/close
+    pub fn refresh_prices(&mut self, pair_ids: Vec<String>) {
+        for pair_id in pair_ids.iter() {
+            require!(self.pair_map.contains_key(pair_id), INVALID_PAIR);
+            let pair = self.pair_map.get(pair_id).unwrap();
+            require!(pair.require_oracle, INVALID_PAIR);
+            self.get_price_for_refresh_prices(&pair);
+        }
+    }
+
     pub fn trigger_bot(&mut self, bot_id: String) {
         require!(self.status == GridStatus::Running, PAUSE_OR_SHUTDOWN);
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
         let mut bot = self.bot_map.get(&bot_id).unwrap().clone();
+        require!(!bot.closed, INVALID_BOT_STATUS);
         require!(bot.active.clone() == false, BOT_IS_ACTIVE);
         let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
         require!(pair.require_oracle, INVALID_PAIR);
-        self.get_price_for_trigger_bot(&pair, &mut bot);
+        if pair.use_cached_price {
+            if let Some((base_price, quote_price)) = self.internal_get_cached_prices(&pair, true) {
+                self.internal_trigger_bot(base_price, quote_price, &env::predecessor_account_id(), &bot_id, &mut bot);
+                return;
+            }
+        }
+        self.get_price_for_trigger_bot(&pair, bot_id, &env::predecessor_account_id());
+    }
+
+    /// anyone can shift a trailing bot's grid one step, when all levels of one side are filled and oracle price is out of the grid
+    pub fn trail_bot(&mut self, bot_id: String) {
+        require!(self.status == GridStatus::Running, PAUSE_OR_SHUTDOWN);
+        require!(self.bot_map.contains_key(&bot_id), BOT_NOT_EXIST);
+        let bot = self.bot_map.get(&bot_id).unwrap();
+        require!(bot.trailing && self.internal_check_bot_can_take(&bot), INVALID_BOT_STATUS);
+        require!(self.internal_get_trail_direction(&bot).is_some(), CAN_NOT_TRAIL);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap().clone();
+        require!(pair.require_oracle, INVALID_PAIR);
+        self.get_price_for_trail_bot(&pair, bot_id);
     }
 
     #[payable]
@@ -153,6 +301,41 @@ impl GridBotContract {
         self.internal_withdraw_all(&user, &token);
     }
 
+    /// wrap attached near, and add to user's wnear balance
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let user = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        require!(amount > 0, INVALID_AMOUNT);
+        require!(self.global_balances_map.contains_key(&self.wnear), INVALID_TOKEN);
+        require!(amount >= self.deposit_limit_map.get(&self.wnear).unwrap().as_u128(), LESS_DEPOSIT_AMOUNT);
+        require!(self.query_user_token_registered(user.clone(), self.wnear.clone()) || self.storage_accounts.contains_key(&user), STORAGE_NOT_REGISTERED);
+        self.deposit_near_to_get_wnear(&user, amount);
+    }
+
+    /// retry the failed payouts of user, anyone can call, always pay to user
+    pub fn retry_withdrawals(&mut self, user: AccountId) {
+        self.internal_retry_withdrawals(&user);
+    }
+
+    /// move the failed payout of token back to balance, when it can't be paid to the same receiver
+    #[payable]
+    pub fn claim_pending_to_balance(&mut self, token: AccountId) {
+        assert_one_yocto();
+        self.internal_claim_pending_to_balance(&env::predecessor_account_id(), &token);
+    }
+
+    /// withdraw part of balance to receiver_id, with ft_transfer_call if msg is set,
+    /// return the amount received, the rest is returned to balance
+    #[payable]
+    pub fn withdraw_to(&mut self, token: AccountId, amount: U128, receiver_id: AccountId, msg: Option<String>) -> Promise {
+        assert_one_yocto();
+        let user = env::predecessor_account_id();
+        require!(amount.0 > 0, INVALID_AMOUNT);
+        require!(self.internal_get_user_balance(&user, &token).as_u128() >= amount.0, INVALID_BALANCE);
+        return self.internal_withdraw_to(&user, &token, amount.0, &receiver_id, msg);
+    }
+
     #[payable]
     pub fn withdraw_refer_fee(&mut self, token: AccountId, amount: U128) {
         assert_one_yocto();
@@ -190,13 +373,7 @@ impl GridBotContract {
     #[payable]
     pub fn withdraw_unowned_asset(&mut self, token: AccountId, to_user: AccountId) {
         self.assert_owner();
-        Promise::new(token.clone())
-            .function_call(
-                "ft_balance_of".to_string(),
-                json!({"account_id": env::current_account_id()}).to_string().into_bytes(),
-                0,
-                Gas(0),
-            )
+        self.internal_token_balance_of(&token)
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
@@ -207,6 +384,21 @@ impl GridBotContract {
             );
     }
 
+    /// compare token accounting with ft_balance_of(current_account)
+    #[payable]
+    pub fn reconcile(&mut self, token: AccountId) {
+        self.assert_owner();
+        require!(self.global_balances_map.contains_key(&token), INVALID_TOKEN);
+        self.internal_token_balance_of(&token)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_ft_balance_of_for_reconcile(
+                        token,
+                    )
+            );
+    }
+
     #[payable]
     pub fn set_protocol_fee_rate(&mut self, new_protocol_fee_rate: U128, new_taker_fee_rate: U128) {
         self.assert_owner();
@@ -248,13 +440,31 @@ impl GridBotContract {
             quote_token: quote_token.clone(),
             base_oracle_id: self.internal_format_price_identifier(base_oracle_id),
             quote_oracle_id: self.internal_format_price_identifier(quote_oracle_id),
-            require_oracle
+            require_oracle,
+            max_conf_rate: 0,
+            oracle_provider: OracleProvider::Pyth,
+            max_deviation_rate: 0,
+            use_cached_price: false,
+            base_token_decimals: None,
+            quote_token_decimals: None,
+            halt_on_stale_price: false,
         };
         self.pair_map.insert(&pair_key, &pair);
+        self.pair_ids.push(&pair_key);
         self.internal_init_token(base_token, base_min_deposit);
         self.internal_init_token(quote_token, quote_min_deposit);
     }
 
+    /// return the token key of NEP-245 token, which is used as token in register_pair
+    #[payable]
+    pub fn register_multi_token(&mut self, contract_id: AccountId, token_id: String) -> AccountId {
+        self.assert_owner();
+        let token = GridBotContract::internal_get_multi_token_key(&contract_id, &token_id);
+        require!(!self.multi_token_map.contains_key(&token), MULTI_TOKEN_EXIST);
+        self.multi_token_map.insert(&token, &MultiToken { contract_id, token_id });
+        return token;
+    }
+
     #[payable]
     pub fn enable_oracle_config(&mut self, base_token: AccountId, quote_token: AccountId, base_oracle_id: String, quote_oracle_id: String) {
         self.assert_owner();
@@ -271,16 +481,75 @@ impl GridBotContract {
         self.pair_map.insert(&pair_key, &pair);
     }
 
+    #[payable]
+    pub fn set_pair_max_conf_rate(&mut self, base_token: AccountId, quote_token: AccountId, max_conf_rate: u16) {
+        self.assert_owner();
+        require!(max_conf_rate <= CONF_RATE_DENOMINATOR, INVALID_CONF_RATE);
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        pair.max_conf_rate = max_conf_rate;
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
+    #[payable]
+    pub fn set_pair_max_deviation_rate(&mut self, base_token: AccountId, quote_token: AccountId, max_deviation_rate: u16) {
+        self.assert_owner();
+        require!(max_deviation_rate <= DEVIATION_RATE_DENOMINATOR, INVALID_DEVIATION_RATE);
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        require!(pair.require_oracle || max_deviation_rate == 0, INVALID_PAIR);
+        require!(GridBotContract::internal_has_token_decimals(&pair) || max_deviation_rate == 0, TOKEN_DECIMALS_NOT_SET);
+        pair.max_deviation_rate = max_deviation_rate;
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
+    #[payable]
+    pub fn set_pair_halt_on_stale_price(&mut self, base_token: AccountId, quote_token: AccountId, halt_on_stale_price: bool) {
+        self.assert_owner();
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        require!(pair.require_oracle || !halt_on_stale_price, INVALID_PAIR);
+        pair.halt_on_stale_price = halt_on_stale_price;
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
+    #[payable]
+    pub fn set_pair_use_cached_price(&mut self, base_token: AccountId, quote_token: AccountId, use_cached_price: bool) {
+        self.assert_owner();
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        require!(pair.require_oracle || !use_cached_price, INVALID_PAIR);
+        pair.use_cached_price = use_cached_price;
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
+    #[payable]
+    pub fn set_pair_token_decimals(&mut self, base_token: AccountId, quote_token: AccountId, base_token_decimals: u8, quote_token_decimals: u8) {
+        self.assert_owner();
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        pair.base_token_decimals = Some(base_token_decimals);
+        pair.quote_token_decimals = Some(quote_token_decimals);
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
     #[payable]
     pub fn set_min_deposit(&mut self, token: AccountId, min_deposit: U128) {
         self.assert_owner();
         self.deposit_limit_map.insert(&token, &U256C::from(min_deposit.0));
     }
 
+    /// renamed from storage_deposit, which is now the NEP-145 storage deposit
     #[payable]
-    pub fn storage_deposit(&mut self, token: AccountId, storage_fee: U128) {
+    pub fn storage_deposit_for_token(&mut self, token: AccountId, storage_fee: U128) {
         require!(env::predecessor_account_id() == self.owner_id, ERR_NOT_ALLOWED);
         require!(env::attached_deposit() == storage_fee.0, LESS_TOKEN_STORAGE_FEE);
+        require!(!self.multi_token_map.contains_key(&token), INVALID_TOKEN);
         self.internal_storage_deposit(&env::current_account_id(), &token, storage_fee.0);
     }
 
@@ -290,6 +559,25 @@ impl GridBotContract {
         self.oracle = new_oracle;
     }
 
+    #[payable]
+    pub fn set_price_oracle(&mut self, new_price_oracle: AccountId) {
+        self.assert_owner();
+        self.price_oracle = Some(new_price_oracle);
+    }
+
+    #[payable]
+    pub fn set_pair_oracle_provider(&mut self, base_token: AccountId, quote_token: AccountId, oracle_provider: OracleProvider) {
+        self.assert_owner();
+        let pair_key = GridBotContract::internal_get_pair_key(base_token.clone(), quote_token.clone());
+        require!(self.pair_map.contains_key(&pair_key), INVALID_PAIR);
+        let mut pair = self.pair_map.get(&pair_key).unwrap();
+        require!(pair.require_oracle, INVALID_PAIR);
+        require!(oracle_provider != OracleProvider::PriceOracle || self.price_oracle.is_some(), INVALID_ORACLE_ID);
+        require!(oracle_provider != OracleProvider::PriceOracle || GridBotContract::internal_has_token_decimals(&pair), TOKEN_DECIMALS_NOT_SET);
+        pair.oracle_provider = oracle_provider;
+        self.pair_map.insert(&pair_key, &pair);
+    }
+
     #[payable]
     pub fn set_oracle_valid_time(&mut self, new_valid_time: u64) {
         self.assert_owner();
@@ -329,6 +617,13 @@ impl GridBotContract {
         self.storage_price_per_byte = new_storage_price_per_byte.0;
     }
 
+    #[payable]
+    pub fn set_expired_close_reward_rate(&mut self, new_expired_close_reward_rate: U128) {
+        self.assert_owner();
+        require!(new_expired_close_reward_rate.0 <= MAX_EXPIRED_CLOSE_REWARD_RATE, INVALID_EXPIRED_CLOSE_REWARD_RATE);
+        self.expired_close_reward_rate = new_expired_close_reward_rate.0;
+    }
+
     #[payable]
     pub fn set_per_grid_storage_fee(&mut self, new_per_grid_storage_fee: U128) {
         self.assert_owner();
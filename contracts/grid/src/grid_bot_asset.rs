@@ -1,7 +1,7 @@
-use near_sdk::{AccountId, Balance, env, require};
+use near_sdk::{AccountId, Balance, env, Promise, require};
 use near_sdk::collections::{LookupMap, Vector};
 use near_sdk::json_types::U128;
-use crate::{GridBot, GridBotContract, PROTOCOL_FEE_DENOMINATOR, StorageKey, TakeRequest, U256C};
+use crate::{CreateBotRequest, GridBot, GridBotContract, PendingWithdrawal, PROTOCOL_FEE_DENOMINATOR, StorageKey, TakeRequest, TokenAccounting, U256C};
 use crate::entity::Pair;
 use crate::events::emit;
 use crate::errors::*;
@@ -14,6 +14,7 @@ impl GridBotContract {
         let new_balance = balance - amount;
         user_balances.insert(token, &new_balance);
         self.user_balances_map.insert(user, &user_balances);
+        self.internal_reduce_token_accounting(token, *amount, |accounting| &mut accounting.free);
     }
 
     pub fn internal_increase_asset(&mut self, user: &AccountId, token: &AccountId, amount: &U256C) {
@@ -27,6 +28,7 @@ impl GridBotContract {
         user_balances.insert(token, &(balance + amount));
 
         self.user_balances_map.insert(user, &user_balances);
+        self.internal_update_token_accounting(token, |accounting| accounting.free += *amount);
     }
 
     pub fn internal_increase_global_asset(&mut self, token: &AccountId, amount: &U256C) {
@@ -45,12 +47,14 @@ impl GridBotContract {
         let balance = self.protocol_fee_map.get(token).unwrap();
         let new_balance = balance + amount;
         self.protocol_fee_map.insert(token, &new_balance);
+        self.internal_update_token_accounting(token, |accounting| accounting.protocol_fee += *amount);
     }
 
     pub fn internal_reduce_protocol_fee(&mut self, token: &AccountId, amount: &U256C) {
         let balance = self.protocol_fee_map.get(token).unwrap();
         let new_balance = balance - amount;
         self.protocol_fee_map.insert(token, &new_balance);
+        self.internal_reduce_token_accounting(token, *amount, |accounting| &mut accounting.protocol_fee);
     }
 
     pub fn internal_increase_locked_assets(&mut self, user: &AccountId, token: &AccountId, amount: &U256C) {
@@ -64,6 +68,7 @@ impl GridBotContract {
         user_locked_balances.insert(token, &(balance + amount));
 
         self.user_locked_balances_map.insert(user, &user_locked_balances);
+        self.internal_update_token_accounting(token, |accounting| accounting.locked += *amount);
     }
 
     pub fn internal_reduce_locked_assets(&mut self, user: &AccountId, token: &AccountId, amount: &U256C) {
@@ -76,6 +81,54 @@ impl GridBotContract {
         let new_balance = balance - amount;
         user_locked_balances.insert(token, &new_balance);
         self.user_locked_balances_map.insert(user, &user_locked_balances);
+        self.internal_reduce_token_accounting(token, *amount, |accounting| &mut accounting.locked);
+    }
+
+    //################################### Token Accounting #########################################
+    pub fn internal_update_token_accounting<F: FnOnce(&mut TokenAccounting)>(&mut self, token: &AccountId, update: F) {
+        let mut accounting = self.token_accounting_map.get(token).unwrap_or_default();
+        update(&mut accounting);
+        self.token_accounting_map.insert(token, &accounting);
+    }
+
+    /// ledger starts empty for assets from before it, reducing more than recorded clamps to zero and emits token_discrepancy
+    pub fn internal_reduce_token_accounting<F: FnOnce(&mut TokenAccounting) -> &mut U256C>(&mut self, token: &AccountId, amount: U256C, field: F) {
+        let mut accounting = self.token_accounting_map.get(token).unwrap_or_default();
+        let recorded = field(&mut accounting);
+        let underflow = match recorded.checked_sub(amount) {
+            Some(new_recorded) => {
+                *recorded = new_recorded;
+                false
+            }
+            None => {
+                *recorded = U256C::from(0);
+                true
+            }
+        };
+        self.token_accounting_map.insert(token, &accounting);
+        if underflow {
+            emit::token_discrepancy(token, accounting.reconciled_balance.as_u128(), self.internal_get_global_balance(token).as_u128(), accounting.owned().as_u128());
+        }
+    }
+
+    /// balance not recorded, transfers in flight are only in global balance
+    pub fn internal_get_unowned_amount(&self, token: &AccountId, balance: U256C) -> U256C {
+        let owned = self.token_accounting_map.get(token).unwrap_or_default().owned();
+        let recorded = std::cmp::max(self.internal_get_global_balance(token), owned);
+        return balance.saturating_sub(recorded);
+    }
+
+    pub fn internal_reconcile(&mut self, token: &AccountId, balance: Balance) {
+        self.internal_update_token_accounting(token, |accounting| {
+            accounting.reconciled_balance = U256C::from(balance);
+            accounting.reconcile_time = env::block_timestamp_ms();
+        });
+        let owned = self.token_accounting_map.get(token).unwrap().owned();
+        let recorded = self.internal_get_global_balance(token);
+        // real balance must cover recorded, recorded is owned plus transfers in flight
+        if U256C::from(balance) < recorded || recorded < owned {
+            emit::token_discrepancy(token, balance, recorded.as_u128(), owned.as_u128());
+        }
     }
 
     //################################### Asset Transfer ###########################################
@@ -159,7 +212,8 @@ impl GridBotContract {
 
     pub fn internal_deposit(&mut self, sender_id: &AccountId, token_in: &AccountId, amount: U128) -> bool {
         require!(self.global_balances_map.contains_key(token_in), INVALID_TOKEN);
-        if !self.query_user_token_registered(sender_id.clone(), token_in.clone()) {
+        // users with NEP-145 storage deposit are registered on first deposit
+        if !self.query_user_token_registered(sender_id.clone(), token_in.clone()) && !self.internal_register_token_with_storage(sender_id, token_in) {
             emit::deposit_failed(sender_id, amount.clone().0, token_in);
             return false;
         }
@@ -206,6 +260,114 @@ impl GridBotContract {
         return U128::from(left);
     }
 
+    /// deposit and create bot in one transfer, storage fee is paid by NEP-145 storage balance,
+    /// return the amount not used by bot
+    pub fn internal_parse_create_bot_request(&mut self, sender_id: &AccountId, token_in: &AccountId, amount: U128, request: CreateBotRequest) -> U128 {
+        // deposit first
+        if !self.internal_deposit(sender_id, token_in, amount) {
+            return amount;
+        }
+        let pair = self.internal_get_create_bot_pair(&request);
+        require!(token_in == &pair.base_token || token_in == &pair.quote_token, INVALID_TOKEN);
+        let slippage = request.slippage;
+        let entry_price = U256C::from(request.entry_price.0);
+        let recommender = request.recommender.clone();
+        let (mut grid_bot, storage_fee) = match self.internal_build_grid_bot(sender_id, &pair, request) {
+            Ok(result) => result,
+            Err(reason) => return self.internal_create_bot_request_refund(sender_id, token_in, amount, &reason),
+        };
+        if self.internal_get_storage_available(sender_id) < storage_fee {
+            return self.internal_create_bot_request_refund(sender_id, token_in, amount, LESS_STORAGE_FEE);
+        }
+        // no NEAR attached, can't wrap near
+        if self.internal_get_user_balance(sender_id, &pair.base_token) < grid_bot.total_base_amount {
+            return self.internal_create_bot_request_refund(sender_id, token_in, amount, LESS_BASE_TOKEN);
+        }
+        if self.internal_get_user_balance(sender_id, &pair.quote_token) < grid_bot.total_quote_amount {
+            return self.internal_create_bot_request_refund(sender_id, token_in, amount, LESS_QUOTE_TOKEN);
+        }
+
+        // return the part of amount the bot doesn't need
+        let bot_amount = if token_in == &pair.base_token { grid_bot.total_base_amount } else { grid_bot.total_quote_amount };
+        let unused = (self.internal_get_user_balance(sender_id, token_in) - bot_amount).as_u128().min(amount.0);
+        if unused > 0 {
+            self.internal_reduce_asset(sender_id, token_in, &(U256C::from(unused)));
+            self.internal_reduce_global_asset(token_in, &(U256C::from(unused)));
+            emit::deposit_return_success(sender_id, unused, token_in);
+        }
+
+        // checked available above, no NEAR attached
+        let storage_drawn = self.internal_draw_storage_balance(sender_id, 0, storage_fee).unwrap();
+        if pair.require_oracle {
+            self.get_price_for_create_bot(&pair, sender_id, slippage, &entry_price, &mut grid_bot, recommender, 0, storage_drawn);
+        } else {
+            self.internal_create_bot(None, None, sender_id, slippage, &entry_price, &pair, recommender, 0, storage_drawn, &mut grid_bot);
+        }
+        return U128::from(unused);
+    }
+
+    /// undo the deposit of create bot request, the amount is returned by ft_resolve_transfer
+    pub fn internal_create_bot_request_refund(&mut self, user: &AccountId, token: &AccountId, amount: U128, reason: &str) -> U128 {
+        self.internal_reduce_asset(user, token, &(U256C::from(amount.0)));
+        self.internal_reduce_global_asset(token, &(U256C::from(amount.0)));
+        emit::create_bot_error(user, reason);
+        return amount;
+    }
+
+    //################################## Pending Withdrawal ##########################################
+    /// failed payout is kept in global balance until retried
+    pub fn internal_add_pending_withdrawal(&mut self, user: &AccountId, token: &AccountId, amount: Balance, reason: &str) {
+        let mut pending_withdrawals = self.pending_withdrawals_map.get(user).unwrap_or_default();
+        if let Some(pending_withdrawal) = pending_withdrawals.iter_mut().find(|pending_withdrawal| &pending_withdrawal.token == token) {
+            pending_withdrawal.amount = U128::from(pending_withdrawal.amount.0 + amount);
+            pending_withdrawal.reason = reason.to_string();
+            pending_withdrawal.update_time = env::block_timestamp_ms();
+        } else {
+            pending_withdrawals.push(PendingWithdrawal {
+                token: token.clone(),
+                amount: U128::from(amount),
+                reason: reason.to_string(),
+                update_time: env::block_timestamp_ms(),
+            });
+        }
+        self.pending_withdrawals_map.insert(user, &pending_withdrawals);
+        self.internal_update_token_accounting(token, |accounting| accounting.pending_withdrawal += U256C::from(amount));
+        emit::pending_withdrawal_added(user, amount, token, reason);
+    }
+
+    pub fn internal_retry_withdrawals(&mut self, user: &AccountId) {
+        let pending_withdrawals = self.pending_withdrawals_map.remove(user).unwrap_or_default();
+        require!(!pending_withdrawals.is_empty(), NO_PENDING_WITHDRAWALS);
+        for pending_withdrawal in pending_withdrawals.iter() {
+            self.internal_reduce_token_accounting(&pending_withdrawal.token, U256C::from(pending_withdrawal.amount.0), |accounting| &mut accounting.pending_withdrawal);
+            if pending_withdrawal.token == self.wnear {
+                // wnear is always paid as near, user needn't register in wnear
+                self.withdraw_near(user, pending_withdrawal.amount.0);
+            } else {
+                self.internal_ft_transfer(user, &pending_withdrawal.token, pending_withdrawal.amount.0);
+            }
+            emit::withdraw_started(user, pending_withdrawal.amount.0, &pending_withdrawal.token);
+        }
+    }
+
+    /// move the pending withdrawal of token back to user's balance, so it can be withdrawn to another receiver
+    pub fn internal_claim_pending_to_balance(&mut self, user: &AccountId, token: &AccountId) {
+        let mut pending_withdrawals = self.pending_withdrawals_map.get(user).unwrap_or_default();
+        let index_op = pending_withdrawals.iter().position(|pending_withdrawal| &pending_withdrawal.token == token);
+        require!(index_op.is_some(), NO_PENDING_WITHDRAWALS);
+        let pending_withdrawal = pending_withdrawals.remove(index_op.unwrap());
+        if pending_withdrawals.is_empty() {
+            self.pending_withdrawals_map.remove(user);
+        } else {
+            self.pending_withdrawals_map.insert(user, &pending_withdrawals);
+        }
+        let amount = U256C::from(pending_withdrawal.amount.0);
+        // still in global balance
+        self.internal_reduce_token_accounting(token, amount, |accounting| &mut accounting.pending_withdrawal);
+        self.internal_increase_asset(user, token, &amount);
+        emit::pending_withdrawal_claimed(user, pending_withdrawal.amount.0, token);
+    }
+
     //################################## Withdraw ##################################################
     pub fn internal_withdraw_all(&mut self, user: &AccountId, token: &AccountId) {
         let balance = self.internal_get_user_balance(user, token);
@@ -232,6 +394,13 @@ impl GridBotContract {
         emit::withdraw_started(user, amount.as_u128(), token);
     }
 
+    pub fn internal_withdraw_to(&mut self, user: &AccountId, token: &AccountId, amount: Balance, receiver_id: &AccountId, msg: Option<String>) -> Promise {
+        // reduce user asset
+        self.internal_reduce_asset(user, token, &(U256C::from(amount)));
+        emit::withdraw_to_started(user, receiver_id, amount, token);
+        return self.internal_ft_transfer_to(user, receiver_id, token, amount, msg);
+    }
+
     pub fn internal_withdraw_protocol_fee(&mut self, user: &AccountId, token: &AccountId, amount: U256C) {
         if amount.as_u128() == 0 {
             return;
@@ -254,11 +423,31 @@ impl GridBotContract {
         emit::withdraw_refer_fee_started(user, amount.0, token);
     }
 
+    /// pay keeper from bot's prepaid NEAR, until it runs out
+    pub fn internal_pay_keeper_reward(&mut self, keeper: &AccountId, bot: &mut GridBot) {
+        let reward = if bot.keeper_reward < bot.keeper_reward_balance { bot.keeper_reward } else { bot.keeper_reward_balance };
+        if reward == U256C::from(0) {
+            return;
+        }
+        bot.keeper_reward_balance -= reward;
+        self.internal_ft_transfer_near(keeper, reward.as_u128(), false);
+        emit::keeper_reward(keeper, bot.bot_id.clone(), reward.as_u128());
+    }
+
     pub fn internal_create_bot_refund_with_near(&mut self, user: &AccountId, pair: &Pair, near_amount: u128, reason: &str) {
         self.internal_create_bot_refund(user, pair, reason);
         self.internal_near_refund(user, near_amount);
     }
 
+    /// storage_drawn goes back to user's storage account
+    pub fn internal_create_bot_refund_with_storage(&mut self, user: &AccountId, pair: &Pair, near_amount: u128, storage_drawn: u128, reason: &str) {
+        self.internal_create_bot_refund(user, pair, reason);
+        if near_amount > 0 {
+            self.internal_near_refund(user, near_amount);
+        }
+        self.internal_return_storage_draw(user, storage_drawn);
+    }
+
     pub fn internal_create_bot_refund(&mut self, user: &AccountId, pair: &Pair, reason: &str) {
         self.internal_withdraw_all(user, &pair.base_token);
         self.internal_withdraw_all(user, &pair.quote_token);
@@ -308,6 +497,7 @@ impl GridBotContract {
             tokens_map.insert(token, &U128::from(tokens_map.get(token).unwrap().0 + amount.clone().0));
         }
         self.refer_fee_map.insert(user, &tokens_map);
+        self.internal_update_token_accounting(token, |accounting| accounting.refer_fee += U256C::from(amount.0));
     }
 
     pub fn internal_reduce_refer_fee(&mut self, user: &AccountId, token: &AccountId, amount: &U128) {
@@ -326,6 +516,7 @@ impl GridBotContract {
             tokens_map.insert(token, &new_refer_fee);
         }
         self.refer_fee_map.insert(user, &tokens_map);
+        self.internal_reduce_token_accounting(token, U256C::from(amount.0), |accounting| &mut accounting.refer_fee);
     }
 
     pub fn internal_allocate_refer_fee(&mut self, protocol_fee: &U256C, user: &AccountId, token: &AccountId) -> (U256C, U256C) {
@@ -389,4 +580,13 @@ impl GridBotContract {
         self.internal_increase_asset(&user, token, &U256C::from(0));
         self.internal_increase_locked_assets(&user, token, &U256C::from(0));
     }
+
+    pub fn internal_unregister_token_for_user(&mut self, user: &AccountId, token: &AccountId) {
+        if let Some(mut user_balances) = self.user_balances_map.get(user) {
+            user_balances.remove(token);
+        }
+        if let Some(mut user_locked_balances) = self.user_locked_balances_map.get(user) {
+            user_locked_balances.remove(token);
+        }
+    }
 }
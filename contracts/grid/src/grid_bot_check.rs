@@ -1,19 +1,22 @@
 use crate::*;
-use near_sdk::{env};
+use near_sdk::{env, require};
 use crate::{GridBotContract, SLIPPAGE_DENOMINATOR};
-use crate::big_decimal::BigDecimal;
 use crate::oracle::{Price};
 
 impl GridBotContract {
 
-    pub fn internal_check_oracle_price(&self, entry_price: U256C, base_price: Price, quote_price: Price, slippage: u16) -> bool {
+    pub fn internal_check_oracle_price(&self, entry_price: U256C, base_price: Price, quote_price: Price, slippage: u16, pair: &Pair) -> bool {
         if base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() < env::block_timestamp_ms() {
             return false;
         }
         if quote_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() < env::block_timestamp_ms() {
             return false;
         }
-        let oracle_pair_price = (BigDecimal::from(base_price.price.0 as u64) / BigDecimal::from(quote_price.price.0 as u64) * BigDecimal::from(PRICE_DENOMINATOR)).round_down_u128();
+        let oracle_pair_price_op = GridBotContract::internal_get_oracle_pair_price(pair, &base_price, &quote_price);
+        if oracle_pair_price_op.is_none() {
+            return false;
+        }
+        let oracle_pair_price = oracle_pair_price_op.unwrap();
 
         if entry_price.as_u128() >= oracle_pair_price {
             return (entry_price.as_u128() - oracle_pair_price) * SLIPPAGE_DENOMINATOR as u128 / entry_price.as_u128() <= slippage as u128;
@@ -22,6 +25,30 @@ impl GridBotContract {
         }
     }
 
+    /// take price must be within pair.max_deviation_rate of the latest cached oracle price, compared as book price
+    /// if cached price is expired, take is rejected when pair.halt_on_stale_price, else not checked
+    pub fn internal_check_take_price_deviation(&self, maker_order: &Order, pair: &Pair) {
+        if !pair.require_oracle || pair.max_deviation_rate == 0 {
+            return;
+        }
+        let pair_price_op = self.internal_get_cached_pair_price(pair, false);
+        if pair_price_op.is_none() {
+            require!(!pair.halt_on_stale_price, PAIR_PRICE_EXPIRED);
+            return;
+        }
+        let book_price_op = GridBotContract::internal_get_oracle_book_price(pair, pair_price_op.unwrap());
+        require!(book_price_op.is_some(), TOKEN_DECIMALS_NOT_SET);
+        let pair_price = U256C::from(book_price_op.unwrap());
+        let take_price = U256C::from(GridBotContract::internal_get_book_price(maker_order, pair));
+        let deviation = if take_price >= pair_price { take_price - pair_price } else { pair_price - take_price };
+        require!(deviation * U256C::from(DEVIATION_RATE_DENOMINATOR) <= pair_price * U256C::from(pair.max_deviation_rate), PRICE_DEVIATION_TOO_LARGE);
+    }
+
+    /// if pair not support oracle, will can't use trigger_price/take_profit_price/stop_loss_price
+    pub fn internal_check_oracle_param(pair: &Pair, trigger_price: u128, take_profit_price: u128, stop_loss_price: u128) -> bool {
+        return pair.require_oracle || trigger_price == 0 && take_profit_price == 0 && stop_loss_price == 0;
+    }
+
     pub fn internal_check_bot_amount(&mut self, grid_sell_count: u16, grid_buy_count: u16, first_base_amount_256: U256C, first_quote_amount_256: U256C,
                                      last_base_amount_256: U256C, last_quote_amount_256: U256C, pair: &Pair, base_amount_sell: U256C, quote_amount_buy: U256C) -> (bool, String) {
         if grid_sell_count > 0 && grid_buy_count > 0 {
@@ -55,7 +82,35 @@ impl GridBotContract {
         return (true, "".to_string());
     }
 
-    pub fn internal_check_bot_close_permission(&self, base_price: Price, quote_price: Price, bot: &GridBot) -> bool {
+    /// both sides need levels, reverse order is placed at the price of neighbour level
+    pub fn internal_check_custom_levels(&self, grid_sell_count: u16, grid_buy_count: u16, custom_levels: &Vec<GridLevel>, pair: &Pair) -> (bool, String) {
+        if grid_sell_count == 0 || grid_buy_count == 0 || custom_levels.len() != (grid_sell_count + grid_buy_count) as usize {
+            return (false, INVALID_CUSTOM_LEVELS.to_string());
+        }
+        let base_min_deposit = self.deposit_limit_map.get(&pair.base_token).unwrap();
+        let quote_min_deposit = self.deposit_limit_map.get(&pair.quote_token).unwrap();
+        for (level, custom_level) in custom_levels.iter().enumerate() {
+            if custom_level.base_amount == U256C::from(0) || custom_level.quote_amount == U256C::from(0) {
+                return (false, INVALID_CUSTOM_LEVELS.to_string());
+            }
+            if level < grid_buy_count as usize && custom_level.quote_amount < quote_min_deposit {
+                return (false, QUOTE_TOO_SMALL.to_string());
+            }
+            if level >= grid_buy_count as usize && custom_level.base_amount < base_min_deposit {
+                return (false, BASE_TOO_SMALL.to_string());
+            }
+            // price must rise with level, quote[i] / base[i] < quote[i + 1] / base[i + 1]
+            if level > 0 {
+                let last_level = &custom_levels[level - 1];
+                if last_level.quote_amount * custom_level.base_amount >= custom_level.quote_amount * last_level.base_amount {
+                    return (false, INVALID_CUSTOM_LEVELS.to_string());
+                }
+            }
+        }
+        return (true, "".to_string());
+    }
+
+    pub fn internal_check_bot_close_permission(&self, base_price: Price, quote_price: Price, bot: &GridBot, pair: &Pair) -> bool {
         if base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() < env::block_timestamp_ms() {
             return false;
         }
@@ -65,13 +120,52 @@ impl GridBotContract {
         // base_price = usd amount / base amount
         // quote_price = usd amount / quote amount
         // oracle_pair_price = quote amount / base amount = base_price / quote_price
-        let oracle_pair_price = (BigDecimal::from(base_price.price.0 as u64) / BigDecimal::from(quote_price.price.0 as u64) * BigDecimal::from(PRICE_DENOMINATOR)).round_down_u128();
+        let oracle_pair_price_op = GridBotContract::internal_get_oracle_pair_price(pair, &base_price, &quote_price);
+        if oracle_pair_price_op.is_none() {
+            return false;
+        }
+        let oracle_pair_price = oracle_pair_price_op.unwrap();
         if oracle_pair_price >= bot.take_profit_price.as_u128() {
             return true;
         }
         if oracle_pair_price <= bot.stop_loss_price.as_u128() {
             return true;
         }
+        // trailing stop, price falls trailing_stop_rate below the peak
+        if bot.trailing_stop_rate > 0 && bot.high_water_price.as_u128() > 0 {
+            let trailing_stop_price = bot.high_water_price * U256C::from(TRAILING_STOP_RATE_DENOMINATOR - bot.trailing_stop_rate) / U256C::from(TRAILING_STOP_RATE_DENOMINATOR);
+            if oracle_pair_price <= trailing_stop_price.as_u128() {
+                return true;
+            }
+        }
         return false;
     }
+
+    /// Some(true): all sell levels filled, grid moves up; Some(false): all buy levels filled, grid moves down
+    pub fn internal_get_trail_direction(&self, bot: &GridBot) -> Option<bool> {
+        let bot_orders = self.order_map.get(&bot.bot_id).unwrap();
+        let grid_buy_count = bot.grid_buy_count as u64;
+        let grid_count = grid_buy_count + bot.grid_sell_count as u64;
+        let side_filled = |levels: std::ops::Range<u64>| -> bool {
+            !levels.is_empty() && levels.into_iter().all(|level| {
+                let order = bot_orders.forward_orders.get(level).unwrap();
+                !GridBotContract::internal_order_is_empty(&order) && GridBotContract::internal_order_is_filled(&order)
+            })
+        };
+        if side_filled(grid_buy_count..grid_count) {
+            return Some(true);
+        }
+        if side_filled(0..grid_buy_count) {
+            return Some(false);
+        }
+        return None;
+    }
+
+    /// same as the checks of query_order, but not panic
+    pub fn internal_check_bot_can_take(&self, bot: &GridBot) -> bool {
+        if bot.closed || !bot.active || bot.paused {
+            return false;
+        }
+        return bot.valid_until_time >= U256C::from(env::block_timestamp_ms());
+    }
 }
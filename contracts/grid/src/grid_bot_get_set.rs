@@ -1,6 +1,6 @@
 use near_sdk::{AccountId, env, Gas};
 use near_sdk::json_types::U128;
-use crate::{GridBot, GridBotContract, GridBotOutput, Pair, U256C};
+use crate::{GridBot, GridBotContract, GridBotOutput, GridStatus, Pair, PairOutput, U256C};
 
 impl GridBotContract {
     pub fn internal_get_next_bot_id(&self) -> u128 {
@@ -63,6 +63,8 @@ impl GridBotContract {
             user: grid.user.clone(),
             bot_id: grid.bot_id.clone(),
             closed: grid.closed.clone(),
+            paused: grid.paused.clone(),
+            trailing: grid.trailing.clone(),
             pair_id: grid.pair_id.clone(),
             grid_type: grid.grid_type.clone(),
             grid_sell_count: grid.grid_sell_count.clone(),
@@ -78,11 +80,28 @@ impl GridBotContract {
             trigger_price_above_or_below: grid.trigger_price_above_or_below.clone(),
             take_profit_price: U128::from(grid.take_profit_price.as_u128()),
             stop_loss_price: U128::from(grid.stop_loss_price.as_u128()),
+            trailing_stop_rate: grid.trailing_stop_rate.clone(),
+            high_water_price: U128::from(grid.high_water_price.as_u128()),
             valid_until_time: U128::from(grid.valid_until_time.as_u128()),
             total_quote_amount: U128::from(grid.total_quote_amount.as_u128()),
             total_base_amount: U128::from(grid.total_base_amount.as_u128()),
             revenue: U128::from(grid.revenue.as_u128()),
             total_revenue: U128::from(grid.total_revenue.as_u128()),
+            keeper_reward: U128::from(grid.keeper_reward.as_u128()),
+            keeper_reward_balance: U128::from(grid.keeper_reward_balance.as_u128()),
+            storage_used_bytes: grid.storage_used_bytes.clone(),
+        }
+    }
+
+    pub fn internal_get_pair_output(&self, pair_id: &String, pair: &Pair) -> PairOutput {
+        PairOutput {
+            pair_id: pair_id.clone(),
+            pair: pair.clone(),
+            base_min_deposit: U128::from(self.deposit_limit_map.get(&pair.base_token).unwrap_or(U256C::from(0)).as_u128()),
+            quote_min_deposit: U128::from(self.deposit_limit_map.get(&pair.quote_token).unwrap_or(U256C::from(0)).as_u128()),
+            bot_count: self.pair_bots_map.get(pair_id).map(|pair_bots| pair_bots.len()).unwrap_or(0),
+            // there is no pause of single pair, all pairs paused with contract
+            paused: self.status != GridStatus::Running,
         }
     }
 }
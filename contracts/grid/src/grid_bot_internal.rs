@@ -1,17 +1,20 @@
 use std::ops::{Add, Div, Mul, Sub};
 use crate::*;
 use near_sdk::{env, require};
+use near_sdk::collections::{TreeMap, Vector};
 use near_sdk::json_types::U128;
 use uint::hex;
 use crate::{GridBotContract};
 use crate::big_decimal::BigDecimal;
 use crate::entity::GridType;
-use crate::entity::GridType::EqOffset;
+use crate::entity::GridType::{Custom, EqOffset};
 use crate::events::emit;
 use crate::oracle::{Price, PriceIdentifier};
 
 impl GridBotContract {
 
+    /// storage_fee: NEAR attached for storage, storage_drawn: NEAR drawn from user's storage balance,
+    /// if storage is drawn, the storage of bot is charged to user's storage account
     pub fn internal_create_bot(&mut self,
                                base_price_op: Option<Price>,
                                quote_price_op: Option<Price>,
@@ -21,24 +24,25 @@ impl GridBotContract {
                                pair: &Pair,
                                recommender: Option<AccountId>,
                                storage_fee: Balance,
+                               storage_drawn: Balance,
                                grid_bot: &mut GridBot) -> bool {
         // record storage fee
         let initial_storage_usage = env::storage_usage();
         if self.status != GridStatus::Running {
-            self.internal_create_bot_refund_with_near(&user, &pair, storage_fee, PAUSE_OR_SHUTDOWN);
+            self.internal_create_bot_refund_with_storage(&user, &pair, storage_fee, storage_drawn, PAUSE_OR_SHUTDOWN);
             return false;
         }
-        if pair.require_oracle && !self.internal_check_oracle_price(*entry_price, base_price_op.clone().unwrap().clone(), quote_price_op.clone().unwrap().clone(), slippage) {
-            self.internal_create_bot_refund_with_near(&user, &pair, storage_fee, INVALID_PRICE);
+        if pair.require_oracle && !self.internal_check_oracle_price(*entry_price, base_price_op.clone().unwrap().clone(), quote_price_op.clone().unwrap().clone(), slippage, pair) {
+            self.internal_create_bot_refund_with_storage(&user, &pair, storage_fee, storage_drawn, INVALID_PRICE);
             return false;
         }
         // check balance
         if self.internal_get_user_balance(user, &(pair.base_token)) < grid_bot.total_base_amount {
-            self.internal_create_bot_refund_with_near(&user, &pair, storage_fee, LESS_BASE_TOKEN);
+            self.internal_create_bot_refund_with_storage(&user, &pair, storage_fee, storage_drawn, LESS_BASE_TOKEN);
             return false;
         }
         if self.internal_get_user_balance(user, &(pair.quote_token)) < grid_bot.total_quote_amount {
-            self.internal_create_bot_refund_with_near(&user, &pair, storage_fee, LESS_QUOTE_TOKEN);
+            self.internal_create_bot_refund_with_storage(&user, &pair, storage_fee, storage_drawn, LESS_QUOTE_TOKEN);
             return false;
         }
 
@@ -59,6 +63,8 @@ impl GridBotContract {
 
         // insert bot
         self.bot_map.insert(&(grid_bot.bot_id), &grid_bot);
+        self.internal_add_bot_to_orderbook(grid_bot, pair);
+        self.internal_add_bot_index(grid_bot);
 
         // add recommender
         self.internal_add_referral_user(recommender.clone(), &user);
@@ -72,10 +78,102 @@ impl GridBotContract {
             emit::create_bot(&grid_bot.user, grid_bot.bot_id.clone(), "0".to_string(), "0".to_string(), "0".to_string(), "0".to_string(), slippage, U128::from(entry_price.as_u128()), pair.clone(), self.internal_get_grid_bot_output(grid_bot));
         }
 
-        self.internal_refund_deposit(storage_fee, initial_storage_usage, &user);
+        // keeper reward stay in contract
+        let storage_paid = storage_fee + storage_drawn - grid_bot.keeper_reward_balance.as_u128();
+        if storage_drawn > 0 {
+            self.internal_charge_bot_storage(&user, grid_bot, storage_paid, initial_storage_usage);
+        } else {
+            self.internal_refund_deposit(storage_paid, initial_storage_usage, &user);
+        }
         return true;
     }
 
+    /// checks of create bot request which panic, return the pair of bot
+    pub fn internal_get_create_bot_pair(&self, request: &CreateBotRequest) -> Pair {
+        require!(self.global_balances_map.contains_key(&self.wnear), INVALID_PAIR);
+        require!(request.valid_until_time.0 > env::block_timestamp_ms() as u128, INVALID_UNTIL_TIME);
+
+        require!(self.pair_map.contains_key(&request.pair_id), INVALID_PAIR_ID);
+        let pair = self.pair_map.get(&request.pair_id).unwrap().clone();
+        // if pair not support oracle, will can't use trigger_price/take_profit_price/stop_loss_price
+        require!(GridBotContract::internal_check_oracle_param(&pair, request.trigger_price.0, request.take_profit_price.0, request.stop_loss_price.0), INVALID_ORACLE_PARAM);
+        return pair;
+    }
+
+    /// build the inactive bot of request, and the storage fee(with prepaid keeper reward) it needs,
+    /// Err is the reason to refund
+    pub fn internal_build_grid_bot(&mut self, user: &AccountId, pair: &Pair, request: CreateBotRequest) -> Result<(GridBot, Balance), String> {
+        let CreateBotRequest {name, pair_id, slippage: _, grid_type, grid_rate, grid_offset, first_base_amount, first_quote_amount,
+            last_base_amount, last_quote_amount, fill_base_or_quote, grid_sell_count, grid_buy_count, trigger_price, take_profit_price,
+            stop_loss_price, valid_until_time, entry_price: _, recommender: _, custom_levels, keeper_reward, keeper_reward_balance} = request;
+        let mut grid_offset_256 = U256C::from(grid_offset.0);
+        let mut first_base_amount_256 = U256C::from(first_base_amount.0);
+        let mut first_quote_amount_256 = U256C::from(first_quote_amount.0);
+        let mut last_base_amount_256 = U256C::from(last_base_amount.0);
+        let mut last_quote_amount_256 = U256C::from(last_quote_amount.0);
+        let trigger_price_256 = U256C::from(trigger_price.0);
+        let take_profit_price_256 = U256C::from(take_profit_price.0);
+        let stop_loss_price_256 = U256C::from(stop_loss_price.0);
+        let valid_until_time_256 = U256C::from(valid_until_time.0);
+        // prepaid in attached NEAR, together with storage fee
+        let keeper_reward_256 = U256C::from(keeper_reward.unwrap_or(U128::from(0)).0);
+        let keeper_reward_balance = keeper_reward_balance.unwrap_or(U128::from(0)).0;
+
+        if self.status != GridStatus::Running {
+            return Err(PAUSE_OR_SHUTDOWN.to_string());
+        }
+
+        // the budget pays one reward at least, and is not locked without reward
+        if keeper_reward_balance < keeper_reward_256.as_u128() || (keeper_reward_256.as_u128() == 0 && keeper_reward_balance > 0) {
+            return Err(INVALID_KEEPER_REWARD.to_string());
+        }
+
+        if grid_buy_count + grid_sell_count > MAX_GRID_COUNT {
+            return Err(MORE_THAN_MAX_GRID_COUNT.to_string());
+        }
+
+        let custom_levels: Vec<GridLevel> = custom_levels.unwrap_or_default().iter().map(|custom_level| custom_level.to_grid_level()).collect();
+        let (base_amount_sell, quote_amount_buy, result, reason) = if grid_type == GridType::Custom {
+            // custom grid use the levels directly, first and last amounts are taken from both ends
+            let (result, reason) = self.internal_check_custom_levels(grid_sell_count, grid_buy_count, &custom_levels, pair);
+            if result {
+                first_base_amount_256 = custom_levels[0].base_amount;
+                first_quote_amount_256 = custom_levels[0].quote_amount;
+                last_base_amount_256 = custom_levels[custom_levels.len() - 1].base_amount;
+                last_quote_amount_256 = custom_levels[custom_levels.len() - 1].quote_amount;
+                grid_offset_256 = U256C::from(0);
+            }
+            let (base_amount_sell, quote_amount_buy) = GridBotContract::internal_calculate_custom_bot_assets(&custom_levels, grid_buy_count);
+            (base_amount_sell, quote_amount_buy, result, reason)
+        } else {
+            // calculate all assets
+            let (base_amount_sell, quote_amount_buy) = GridBotContract::internal_calculate_bot_assets(first_quote_amount_256.clone(), last_base_amount_256.clone(), grid_sell_count.clone(), grid_buy_count.clone(),
+                                                           grid_type.clone(), grid_rate.clone(), grid_offset_256.clone(), fill_base_or_quote.clone());
+
+            // last_quote_amount / last_base_amount > first_quote_amount > first_base_amount
+            // amount must u128, u128 * u128 <= u256, so, it's ok
+            let (result, reason) = self.internal_check_bot_amount(grid_sell_count, grid_buy_count, first_base_amount_256, first_quote_amount_256,
+                                                                last_base_amount_256, last_quote_amount_256, pair, base_amount_sell, quote_amount_buy);
+            (base_amount_sell, quote_amount_buy, result, reason)
+        };
+        if !result {
+            return Err(reason);
+        }
+
+        let grid_bot = GridBot {name, active: false, user: user.clone(), bot_id: "".to_string(), closed: false, paused: false, trailing: false, pair_id, grid_type,
+            grid_sell_count: grid_sell_count.clone(), grid_buy_count: grid_buy_count.clone(), grid_rate, grid_offset: grid_offset_256,
+            first_base_amount: first_base_amount_256, first_quote_amount: first_quote_amount_256, last_base_amount: last_base_amount_256,
+            last_quote_amount: last_quote_amount_256, fill_base_or_quote, trigger_price: trigger_price_256, trigger_price_above_or_below: false,
+            take_profit_price: take_profit_price_256, stop_loss_price: stop_loss_price_256,
+            trailing_stop_rate: 0, high_water_price: U256C::from(0), valid_until_time: valid_until_time_256,
+            total_quote_amount: quote_amount_buy, total_base_amount: base_amount_sell, revenue: U256C::from(0), total_revenue: U256C::from(0),
+            keeper_reward: keeper_reward_256, keeper_reward_balance: U256C::from(keeper_reward_balance), custom_levels,
+            storage_used_bytes: 0,
+        };
+        let storage_fee = self.base_create_storage_fee + self.per_grid_storage_fee * (grid_buy_count + grid_sell_count) as u128 + keeper_reward_balance;
+        return Ok((grid_bot, storage_fee));
+    }
+
     pub fn internal_take_orders(&mut self, user: &AccountId, take_order: &Order, maker_orders: Vec<OrderKeyInfo>) -> (U256C, U256C) {
         require!(self.status == GridStatus::Running, PAUSE_OR_SHUTDOWN);
         require!(maker_orders.len() > 0, INVALID_MAKER_ORDERS);
@@ -114,12 +212,16 @@ impl GridBotContract {
         return (took_amount_sell, took_amount_buy);
     }
 
-    pub fn internal_close_bot(&mut self, sender: &AccountId, bot_id: &String, bot: &mut GridBot, pair: &Pair) {
+    /// storage_reward_rate: the share of storage refund paid to sender, real rate = storage_reward_rate / 1000000
+    pub fn internal_close_bot(&mut self, sender: &AccountId, bot_id: &String, bot: &mut GridBot, pair: &Pair, storage_reward_rate: u128) {
         // record storage fee
         let initial_storage_usage = env::storage_usage();
 
         // sign closed
         bot.closed = true;
+        // unused keeper reward back to user
+        let keeper_reward_refund = bot.keeper_reward_balance.as_u128();
+        bot.keeper_reward_balance = U256C::from(0);
 
         // harvest revenue, must fist execute, will split revenue from bot's asset
         let (revenue_token, revenue) = self.internal_harvest_revenue(bot, pair);
@@ -133,15 +235,36 @@ impl GridBotContract {
         self.internal_withdraw(&(bot.user), &revenue_token, revenue);
         // update bot info
         self.bot_map.insert(bot_id, &bot);
+        // remove from orderbook and indexes
+        self.internal_remove_bot_from_orderbook(bot, pair);
+        self.internal_remove_bot_index(bot);
         // clear bots orders
         let mut order_storage = self.order_map.get(bot_id).unwrap();
         order_storage.forward_orders.clear();
         order_storage.reverse_orders.clear();
         self.order_map.remove(bot_id);
 
-        // Refund
-        let refund = self.storage_price_per_byte * Balance::from(initial_storage_usage - env::storage_usage());
-        self.internal_ft_transfer_near(&(bot.user), refund, false);
+        // Refund, storage charged to storage account is released there
+        let charged_to_storage_account = bot.storage_used_bytes > 0 && self.storage_accounts.contains_key(&bot.user);
+        let storage_refund = if charged_to_storage_account {
+            self.storage_price_per_byte * Balance::from(bot.storage_used_bytes)
+        } else {
+            self.storage_price_per_byte * Balance::from(initial_storage_usage - env::storage_usage())
+        };
+        let storage_reward = storage_refund * storage_reward_rate / PROTOCOL_FEE_DENOMINATOR;
+        if storage_reward > 0 {
+            self.internal_ft_transfer_near(sender, storage_reward, false);
+            emit::keeper_reward(sender, bot_id.clone(), storage_reward);
+        }
+        let refund = if charged_to_storage_account {
+            self.internal_release_bot_storage(&(bot.user), bot, storage_reward);
+            keeper_reward_refund
+        } else {
+            storage_refund - storage_reward + keeper_reward_refund
+        };
+        if refund > 0 {
+            self.internal_ft_transfer_near(&(bot.user), refund, false);
+        }
 
         // send claim event
         if revenue.as_u128() > 0 {
@@ -151,25 +274,131 @@ impl GridBotContract {
         emit::close_bot(sender, bot_id.clone(), refund);
     }
 
+    /// move assets between user's balance and bot, and scale the grid and orders by (denominator ± numerator) / denominator.
+    /// numerator / denominator is the lower of base_amount / total_base_amount and quote_amount / total_quote_amount
+    pub fn internal_change_bot_assets(&mut self, bot: &mut GridBot, pair: &Pair, base_amount: U256C, quote_amount: U256C, increase: bool) -> (U256C, U256C) {
+        // revenue is split out first, only the grid inventory is scaled
+        let (revenue_token, revenue) = self.internal_harvest_revenue(bot, pair);
+        if revenue.as_u128() > 0 {
+            emit::claim(&(bot.user), &(bot.user), bot.bot_id.clone(), &revenue_token, revenue);
+        }
+        require!(bot.total_base_amount > U256C::from(0) || bot.total_quote_amount > U256C::from(0), INVALID_AMOUNT);
+        let (numerator, denominator) = if bot.total_base_amount == U256C::from(0) {
+            (quote_amount, bot.total_quote_amount)
+        } else if bot.total_quote_amount == U256C::from(0) {
+            (base_amount, bot.total_base_amount)
+        } else if base_amount * bot.total_quote_amount <= quote_amount * bot.total_base_amount {
+            (base_amount, bot.total_base_amount)
+        } else {
+            (quote_amount, bot.total_quote_amount)
+        };
+        require!(numerator > U256C::from(0), INVALID_AMOUNT);
+        // decrease all assets means close bot
+        require!(increase || numerator < denominator, INVALID_AMOUNT);
+        let change_base_amount = bot.total_base_amount * numerator / denominator;
+        let change_quote_amount = bot.total_quote_amount * numerator / denominator;
+        let scale_numerator = if increase { denominator + numerator } else { denominator - numerator };
+
+        // book price of scaled orders may change by rounding, so take the bot out of orderbook first
+        self.internal_remove_bot_from_orderbook(bot, pair);
+        if increase {
+            require!(self.internal_get_user_balance(&(bot.user), &(pair.base_token)) >= change_base_amount, LESS_BASE_TOKEN);
+            require!(self.internal_get_user_balance(&(bot.user), &(pair.quote_token)) >= change_quote_amount, LESS_QUOTE_TOKEN);
+            self.internal_transfer_assets_to_lock(&(bot.user), &(pair.base_token), change_base_amount);
+            self.internal_transfer_assets_to_lock(&(bot.user), &(pair.quote_token), change_quote_amount);
+            bot.total_base_amount += change_base_amount;
+            bot.total_quote_amount += change_quote_amount;
+        } else {
+            self.internal_transfer_assets_to_unlock(&(bot.user), &(pair.base_token), change_base_amount);
+            self.internal_transfer_assets_to_unlock(&(bot.user), &(pair.quote_token), change_quote_amount);
+            bot.total_base_amount -= change_base_amount;
+            bot.total_quote_amount -= change_quote_amount;
+        }
+        self.internal_scale_bot(bot, scale_numerator, denominator);
+        if !increase && bot.grid_type == Custom {
+            let (result, reason) = self.internal_check_custom_levels(bot.grid_sell_count, bot.grid_buy_count, &bot.custom_levels, pair);
+            require!(result, reason);
+        } else if !increase {
+            let (base_amount_sell, quote_amount_buy) = GridBotContract::internal_calculate_bot_assets(bot.first_quote_amount.clone(), bot.last_base_amount.clone(), bot.grid_sell_count.clone(), bot.grid_buy_count.clone(),
+                                                                                                     bot.grid_type.clone(), bot.grid_rate.clone(), bot.grid_offset.clone(), bot.fill_base_or_quote.clone());
+            let (result, reason) = self.internal_check_bot_amount(bot.grid_sell_count, bot.grid_buy_count, bot.first_base_amount, bot.first_quote_amount,
+                                                                  bot.last_base_amount, bot.last_quote_amount, pair, base_amount_sell, quote_amount_buy);
+            require!(result, reason);
+        }
+        self.internal_restore_bot_to_orderbook(bot, pair);
+        self.bot_map.insert(&(bot.bot_id), bot);
+        return (change_base_amount, change_quote_amount);
+    }
+
+    /// grid params and orders multiply numerator / denominator, round down
+    pub fn internal_scale_bot(&mut self, bot: &mut GridBot, numerator: U256C, denominator: U256C) {
+        bot.grid_offset = bot.grid_offset * numerator / denominator;
+        bot.first_base_amount = bot.first_base_amount * numerator / denominator;
+        bot.first_quote_amount = bot.first_quote_amount * numerator / denominator;
+        bot.last_base_amount = bot.last_base_amount * numerator / denominator;
+        bot.last_quote_amount = bot.last_quote_amount * numerator / denominator;
+        for custom_level in bot.custom_levels.iter_mut() {
+            custom_level.base_amount = custom_level.base_amount * numerator / denominator;
+            custom_level.quote_amount = custom_level.quote_amount * numerator / denominator;
+        }
+
+        let mut order_storage = self.order_map.get(&(bot.bot_id)).unwrap();
+        for index in 0..order_storage.forward_orders.len() {
+            let order = order_storage.forward_orders.get(index).unwrap();
+            if !GridBotContract::internal_order_is_empty(&order) {
+                order_storage.forward_orders.replace(index, &GridBotContract::internal_scale_order(&order, numerator, denominator));
+            }
+        }
+        for index in 0..order_storage.reverse_orders.len() {
+            let order = order_storage.reverse_orders.get(index).unwrap();
+            if !GridBotContract::internal_order_is_empty(&order) {
+                order_storage.reverse_orders.replace(index, &GridBotContract::internal_scale_order(&order, numerator, denominator));
+            }
+        }
+    }
+
+    /// scale the remaining part rather than filled, so a partially filled order keeps partially filled
+    pub fn internal_scale_order(order: &Order, numerator: U256C, denominator: U256C) -> Order {
+        let mut new_order = order.clone();
+        new_order.amount_sell = order.amount_sell * numerator / denominator;
+        new_order.amount_buy = order.amount_buy * numerator / denominator;
+        let (amount_fill, new_amount_fill) = if order.fill_buy_or_sell {
+            (order.amount_buy, new_order.amount_buy)
+        } else {
+            (order.amount_sell, new_order.amount_sell)
+        };
+        let remaining = if order.filled >= amount_fill { U256C::from(0) } else { amount_fill - order.filled };
+        new_order.filled = new_amount_fill - remaining * numerator / denominator;
+        return new_order;
+    }
+
     pub fn internal_auto_close_bot(&mut self, base_price: Price, quote_price: Price, user: &AccountId, bot_id: &String, bot: &mut GridBot, pair: &Pair) {
-        require!(self.internal_check_bot_close_permission(base_price.clone(), quote_price.clone(), bot), INVALID_PRICE_OR_NO_PERMISSION);
+        require!(self.internal_check_bot_close_permission(base_price.clone(), quote_price.clone(), bot, pair), INVALID_PRICE_OR_NO_PERMISSION);
         emit::close_bot_price(base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
-        self.internal_close_bot(user, bot_id, bot, pair);
+        self.internal_pay_keeper_reward(user, bot);
+        self.internal_close_bot(user, bot_id, bot, pair, 0);
     }
 
-    pub fn internal_trigger_bot(&mut self, base_price: Price, quote_price: Price, bot_id: &String, bot: &mut GridBot) {
+    pub fn internal_trigger_bot(&mut self, base_price: Price, quote_price: Price, keeper: &AccountId, bot_id: &String, bot: &mut GridBot) {
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        require!(!bot.active, BOT_IS_ACTIVE);
         require!(base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
         require!(quote_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
-        let oracle_pair_price = (BigDecimal::from(base_price.price.0 as u64) / BigDecimal::from(quote_price.price.0 as u64) * BigDecimal::from(PRICE_DENOMINATOR)).round_down_u128();
+        let pair = self.pair_map.get(&bot.pair_id).unwrap();
+        let oracle_pair_price_op = GridBotContract::internal_get_oracle_pair_price(&pair, &base_price, &quote_price);
+        require!(oracle_pair_price_op.is_some(), INVALID_PRICE);
+        let oracle_pair_price = oracle_pair_price_op.unwrap();
 
         if bot.trigger_price_above_or_below.clone() && bot.trigger_price.clone().as_u128() <= oracle_pair_price {
             // self.bot_map.get_mut(&bot_id).unwrap().active = true;
             bot.active = true;
+            self.internal_pay_keeper_reward(keeper, bot);
             self.bot_map.insert(&bot_id, &bot);
             emit::trigger_bot(bot_id.clone(), base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
         } else if !bot.trigger_price_above_or_below.clone() && bot.trigger_price.clone().as_u128() >= oracle_pair_price {
             // self.bot_map.get_mut(&bot_id).unwrap().active = true;
             bot.active = true;
+            self.internal_pay_keeper_reward(keeper, bot);
             self.bot_map.insert(&bot_id, &bot);
             emit::trigger_bot(bot_id.clone(), base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
         } else {
@@ -177,6 +406,159 @@ impl GridBotContract {
         }
     }
 
+    pub fn internal_update_high_water_price(&mut self, base_price: Price, quote_price: Price, bot_id: &String, bot: &mut GridBot) {
+        require!(base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        require!(quote_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap();
+        let oracle_pair_price_op = GridBotContract::internal_get_oracle_pair_price(&pair, &base_price, &quote_price);
+        require!(oracle_pair_price_op.is_some(), INVALID_PRICE);
+        let oracle_pair_price = oracle_pair_price_op.unwrap();
+        if oracle_pair_price <= bot.high_water_price.as_u128() {
+            return;
+        }
+        bot.high_water_price = U256C::from(oracle_pair_price);
+        self.bot_map.insert(bot_id, bot);
+        emit::update_high_water_price(bot_id.clone(), U128::from(oracle_pair_price), base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
+    }
+
+    pub fn internal_refresh_prices(&mut self, base_price: Price, quote_price: Price, pair_id: &String, pair: &Pair) {
+        require!(base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        require!(quote_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        require!(GridBotContract::internal_get_oracle_pair_price(pair, &base_price, &quote_price).is_some(), INVALID_PRICE);
+        self.internal_record_price(pair.base_oracle_id.as_ref().unwrap(), &base_price);
+        self.internal_record_price(pair.quote_oracle_id.as_ref().unwrap(), &quote_price);
+        emit::refresh_prices(pair_id.clone(), base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
+    }
+
+    pub fn internal_trail_bot(&mut self, base_price: Price, quote_price: Price, bot_id: &String, bot: &mut GridBot, pair: &Pair) {
+        require!(base_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        require!(quote_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() >= env::block_timestamp_ms(), INVALID_PRICE);
+        let oracle_pair_price_op = GridBotContract::internal_get_oracle_pair_price(pair, &base_price, &quote_price);
+        require!(oracle_pair_price_op.is_some(), INVALID_PRICE);
+        // compare with book price
+        let oracle_book_price_op = GridBotContract::internal_get_oracle_book_price(pair, oracle_pair_price_op.unwrap());
+        require!(oracle_book_price_op.is_some(), TOKEN_DECIMALS_NOT_SET);
+        let oracle_pair_price = oracle_book_price_op.unwrap();
+
+        let up_op = self.internal_get_trail_direction(bot);
+        require!(up_op.is_some(), CAN_NOT_TRAIL);
+        let up = up_op.unwrap();
+        // price must be out of the grid on the filled side
+        let grid_count = (bot.grid_buy_count + bot.grid_sell_count) as usize;
+        if up {
+            let top_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), grid_count - 1);
+            require!(oracle_pair_price > GridBotContract::internal_get_book_price(&top_order, pair), CAN_NOT_TRAIL);
+        } else {
+            let bottom_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), 0);
+            require!(oracle_pair_price < GridBotContract::internal_get_book_price(&bottom_order, pair), CAN_NOT_TRAIL);
+        }
+
+        // book keys of virtual orders come from grid params, remove before moving
+        self.internal_remove_bot_from_orderbook(bot, pair);
+        require!(GridBotContract::internal_shift_grid(bot, up), CAN_NOT_TRAIL);
+        self.internal_reprice_bot_orders(bot, pair);
+        self.internal_restore_bot_to_orderbook(bot, pair);
+        self.bot_map.insert(bot_id, bot);
+        emit::trail_bot(bot_id.clone(), up, base_price.price.0.to_string(), quote_price.price.0.to_string(), base_price.expo.to_string(), quote_price.expo.to_string());
+    }
+
+    /// move every level's price one step, false if the grid can't move further
+    pub fn internal_shift_grid(bot: &mut GridBot, up: bool) -> bool {
+        let grid_rate_denominator_256 = U256C::from(GRID_RATE_DENOMINATOR);
+        let grid_rate_numerator_256 = grid_rate_denominator_256 + U256C::from(bot.grid_rate);
+        let grid_buy_count_256 = U256C::from(bot.grid_buy_count);
+        let grid_sell_count_256 = U256C::from(bot.grid_sell_count);
+        if bot.grid_type == Custom {
+            return false;
+        }
+        if bot.fill_base_or_quote {
+            // fixed base, price move with quote amount
+            if bot.grid_type == EqOffset {
+                if up {
+                    bot.first_quote_amount += bot.grid_offset;
+                    bot.last_quote_amount += bot.grid_offset;
+                } else {
+                    if (bot.grid_buy_count > 0 && bot.first_quote_amount <= bot.grid_offset) || (bot.grid_sell_count > 0 && bot.last_quote_amount <= bot.grid_offset * grid_sell_count_256) {
+                        return false;
+                    }
+                    bot.first_quote_amount = bot.first_quote_amount.saturating_sub(bot.grid_offset);
+                    bot.last_quote_amount = bot.last_quote_amount.saturating_sub(bot.grid_offset);
+                }
+            } else if up {
+                bot.first_quote_amount = bot.first_quote_amount * grid_rate_numerator_256 / grid_rate_denominator_256;
+                bot.last_quote_amount = bot.last_quote_amount * grid_rate_numerator_256 / grid_rate_denominator_256;
+            } else {
+                bot.first_quote_amount = bot.first_quote_amount * grid_rate_denominator_256 / grid_rate_numerator_256;
+                bot.last_quote_amount = bot.last_quote_amount * grid_rate_denominator_256 / grid_rate_numerator_256;
+            }
+        } else {
+            // fixed quote, price rise when base amount fall
+            if bot.grid_type == EqOffset {
+                if up {
+                    if (bot.grid_buy_count > 0 && bot.first_base_amount <= bot.grid_offset * grid_buy_count_256) || (bot.grid_sell_count > 0 && bot.last_base_amount <= bot.grid_offset) {
+                        return false;
+                    }
+                    bot.first_base_amount = bot.first_base_amount.saturating_sub(bot.grid_offset);
+                    bot.last_base_amount = bot.last_base_amount.saturating_sub(bot.grid_offset);
+                } else {
+                    bot.first_base_amount += bot.grid_offset;
+                    bot.last_base_amount += bot.grid_offset;
+                }
+            } else if up {
+                bot.first_base_amount = bot.first_base_amount * grid_rate_denominator_256 / grid_rate_numerator_256;
+                bot.last_base_amount = bot.last_base_amount * grid_rate_denominator_256 / grid_rate_numerator_256;
+            } else {
+                bot.first_base_amount = bot.first_base_amount * grid_rate_numerator_256 / grid_rate_denominator_256;
+                bot.last_base_amount = bot.last_base_amount * grid_rate_numerator_256 / grid_rate_denominator_256;
+            }
+        }
+        let zero = U256C::from(0);
+        if bot.grid_buy_count > 0 && (bot.first_base_amount == zero || bot.first_quote_amount == zero) {
+            return false;
+        }
+        if bot.grid_sell_count > 0 && (bot.last_base_amount == zero || bot.last_quote_amount == zero) {
+            return false;
+        }
+        return true;
+    }
+
+    /// re-derive every placed slot from the moved grid
+    /// orders not filled keep their remaining sell amount, and take the price of the moved grid
+    /// filled orders are replaced by the filled order of the moved grid, so the orders merged into them later keep one price
+    pub fn internal_reprice_bot_orders(&mut self, bot: &GridBot, pair: &Pair) {
+        let order_storage = self.order_map.get(&(bot.bot_id)).unwrap();
+        let mut forward_orders = order_storage.forward_orders;
+        let mut reverse_orders = order_storage.reverse_orders;
+        for level in 0..forward_orders.len() {
+            let forward_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level as usize);
+            let placed_forward_order = forward_orders.get(level).unwrap();
+            if !GridBotContract::internal_order_is_empty(&placed_forward_order) {
+                forward_orders.replace(level, &GridBotContract::internal_reprice_order(&placed_forward_order, &forward_order));
+            }
+            let placed_reverse_order = reverse_orders.get(level).unwrap();
+            if !GridBotContract::internal_order_is_empty(&placed_reverse_order) {
+                let reverse_order = GridBotContract::internal_get_opposite_order(&forward_order, bot.clone(), true, level as usize);
+                reverse_orders.replace(level, &GridBotContract::internal_reprice_order(&placed_reverse_order, &reverse_order));
+            }
+        }
+    }
+
+    /// new order sell the remaining of order, at the price of template
+    /// filled order becomes the filled template
+    pub fn internal_reprice_order(order: &Order, template: &Order) -> Order {
+        let (remaining_sell, _) = GridBotContract::internal_get_order_remaining(order);
+        if GridBotContract::internal_order_is_filled(order) || remaining_sell == U256C::from(0) {
+            let mut new_order = template.clone();
+            new_order.filled = if new_order.fill_buy_or_sell { new_order.amount_buy } else { new_order.amount_sell };
+            return new_order;
+        }
+        let mut new_order = order.clone();
+        new_order.amount_sell = remaining_sell;
+        new_order.amount_buy = U256C::from(BigDecimal::from(template.amount_buy.as_u128()).mul(BigDecimal::from(remaining_sell.as_u128())).div(BigDecimal::from(template.amount_sell.as_u128())).round_up_u128());
+        new_order.filled = U256C::from(0);
+        return new_order;
+    }
+
     pub fn internal_add_referral_user(&mut self, recommender_op: Option<AccountId>, user: &AccountId) {
         if self.refer_user_recommender_map.contains_key(user) || recommender_op.is_none() || user.clone() == recommender_op.clone().unwrap() {
             return;
@@ -195,6 +577,46 @@ impl GridBotContract {
         self.internal_increase_refer_fee(&recommender, quote_token, &U128::from(0), true);
     }
 
+    pub fn internal_add_bot_index(&mut self, bot: &GridBot) {
+        let mut user_bots = self.user_bots_map.get(&bot.user).unwrap_or_else(|| {
+            Vector::new(StorageKey::UserBotsSubKey(bot.user.clone()))
+        });
+        user_bots.push(&bot.bot_id);
+        self.user_bots_map.insert(&bot.user, &user_bots);
+
+        let bot_seq = self.internal_get_bot_seq(&bot.bot_id);
+        let mut user_open_bots = self.user_open_bots_map.get(&bot.user).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::UserOpenBotsSubKey(bot.user.clone()))
+        });
+        user_open_bots.insert(&bot_seq, &bot.bot_id);
+        self.user_open_bots_map.insert(&bot.user, &user_open_bots);
+
+        let mut pair_bots = self.pair_bots_map.get(&bot.pair_id).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::PairBotsSubKey(bot.pair_id.clone()))
+        });
+        pair_bots.insert(&bot_seq, &bot.bot_id);
+        self.pair_bots_map.insert(&bot.pair_id, &pair_bots);
+    }
+
+    pub fn internal_remove_bot_index(&mut self, bot: &GridBot) {
+        let bot_seq = self.internal_get_bot_seq(&bot.bot_id);
+        if let Some(mut user_open_bots) = self.user_open_bots_map.get(&bot.user) {
+            user_open_bots.remove(&bot_seq);
+            self.user_open_bots_map.insert(&bot.user, &user_open_bots);
+        }
+        if let Some(mut pair_bots) = self.pair_bots_map.get(&bot.pair_id) {
+            pair_bots.remove(&bot_seq);
+            self.pair_bots_map.insert(&bot.pair_id, &pair_bots);
+        }
+    }
+
+    /// bot_id = GRID:bot_seq
+    pub fn internal_get_bot_seq(&self, bot_id: &String) -> u128 {
+        let bot_seq = bot_id.strip_prefix("GRID:").and_then(|seq| seq.parse::<u128>().ok());
+        require!(bot_seq.is_some(), INVALID_BOT_ID);
+        return bot_seq.unwrap();
+    }
+
     pub fn internal_get_and_use_next_bot_id(&mut self) -> u128 {
         let next_id = self.next_bot_id.clone();
 
@@ -227,6 +649,21 @@ impl GridBotContract {
             fill_buy_or_sell: false,
             filled: U256C::from(0),
         };
+        if grid_bot.grid_type == Custom {
+            let custom_level = grid_bot.custom_levels[level].clone();
+            if grid_bot.grid_buy_count > (level.clone() as u16) {
+                order.token_sell = pair.quote_token.clone();
+                order.token_buy = pair.base_token.clone();
+                order.amount_sell = custom_level.quote_amount;
+                order.amount_buy = custom_level.base_amount;
+                order.fill_buy_or_sell = grid_bot.fill_base_or_quote.clone();
+            } else {
+                order.amount_sell = custom_level.base_amount;
+                order.amount_buy = custom_level.quote_amount;
+                order.fill_buy_or_sell = !grid_bot.fill_base_or_quote.clone();
+            }
+            return order;
+        }
         // let grid_rate_denominator_128 = U256C::from(GRID_RATE_DENOMINATOR);
         let grid_rate_denominator_256 = U256C::from(GRID_RATE_DENOMINATOR);
         if grid_bot.grid_buy_count > (level.clone() as u16) {
@@ -324,6 +761,20 @@ impl GridBotContract {
         return (base_amount_sell, quote_amount_buy);
     }
 
+    /// quote of buy levels and base of sell levels
+    pub fn internal_calculate_custom_bot_assets(custom_levels: &Vec<GridLevel>, grid_buy_count: u16) -> (U256C, U256C) {
+        let mut base_amount_sell = U256C::from(0);
+        let mut quote_amount_buy = U256C::from(0);
+        for (level, custom_level) in custom_levels.iter().enumerate() {
+            if level < grid_buy_count as usize {
+                quote_amount_buy += custom_level.quote_amount;
+            } else {
+                base_amount_sell += custom_level.base_amount;
+            }
+        }
+        return (base_amount_sell, quote_amount_buy);
+    }
+
     // pub fn create_default_orders(bot_id: String, grid_count: u16) -> Vector<Vector<Order>> {
     //     let mut outer_vector = Vector::new(StorageKey::OrdersMainKey(bot_id));
     //     for i in 0..2 {
@@ -358,6 +809,10 @@ impl GridBotContract {
         self.global_balances_map.insert(&token, &U256C::from(0));
         self.protocol_fee_map.insert(&token, &U256C::from(0));
         self.deposit_limit_map.insert(&token, &U256C::from(min_deposit.0));
+        if self.multi_token_map.contains_key(&token) {
+            // NEP-245 has no storage management
+            return U256C::from(0);
+        }
         self.internal_storage_deposit(&env::current_account_id(), &token, DEFAULT_TOKEN_STORAGE_FEE);
         return U256C::from(DEFAULT_TOKEN_STORAGE_FEE);
     }
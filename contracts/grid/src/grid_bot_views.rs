@@ -18,6 +18,46 @@ impl GridBotContract {
         return grid_bots;
     }
 
+    /// from: index start from 0, in created order
+    /// include_closed: true for all bots of user, false for not closed bots only, same as query_user_bots_count
+    pub fn query_user_bots(&self, user: AccountId, from: u64, limit: u64, include_closed: bool) -> Vec<GridBotOutput> {
+        let bot_ids: Vec<String> = if include_closed {
+            match self.user_bots_map.get(&user) {
+                Some(user_bots) => (from..std::cmp::min(from + limit, user_bots.len())).map(|index| user_bots.get(index).unwrap()).collect(),
+                None => vec![],
+            }
+        } else {
+            match self.user_open_bots_map.get(&user) {
+                Some(user_open_bots) => user_open_bots.iter().skip(from as usize).take(limit as usize).map(|(_, bot_id)| bot_id).collect(),
+                None => vec![],
+            }
+        };
+        return self.query_bots(bot_ids);
+    }
+
+    pub fn query_user_bots_count(&self, user: AccountId, include_closed: bool) -> u64 {
+        if include_closed {
+            return self.user_bots_map.get(&user).map(|user_bots| user_bots.len()).unwrap_or(0);
+        }
+        return self.user_open_bots_map.get(&user).map(|user_open_bots| user_open_bots.len()).unwrap_or(0);
+    }
+
+    /// not closed bots of pair in created order
+    /// from_bot_id: cursor, the bots created after it are returned, None for the first page
+    pub fn query_pair_bots(&self, pair_id: String, from_bot_id: Option<String>, limit: u64) -> Vec<GridBotOutput> {
+        require!(self.pair_map.contains_key(&pair_id), INVALID_PAIR_ID);
+        let pair_bots_op = self.pair_bots_map.get(&pair_id);
+        if pair_bots_op.is_none() {
+            return vec![];
+        }
+        let pair_bots = pair_bots_op.unwrap();
+        let bot_ids: Vec<String> = match from_bot_id {
+            Some(from_bot_id) => pair_bots.iter_from(self.internal_get_bot_seq(&from_bot_id)).take(limit as usize).map(|(_, bot_id)| bot_id).collect(),
+            None => pair_bots.iter().take(limit as usize).map(|(_, bot_id)| bot_id).collect(),
+        };
+        return self.query_bots(bot_ids);
+    }
+
     pub fn query_protocol_fee(&self, token: AccountId) -> U128 {
         require!(self.protocol_fee_map.contains_key(&token), INVALID_TOKEN);
         return U128::from(self.internal_get_protocol_fee(&token).as_u128());
@@ -33,6 +73,30 @@ impl GridBotContract {
         return U128::from(self.internal_get_user_balance(&user, &token).as_u128());
     }
 
+    /// totals of token, unowned is of the last reconcile
+    pub fn query_token_accounting(&self, token: AccountId) -> TokenAccountingOutput {
+        let accounting = self.token_accounting_map.get(&token).unwrap_or_default();
+        return TokenAccountingOutput {
+            free: U128::from(accounting.free.as_u128()),
+            locked: U128::from(accounting.locked.as_u128()),
+            protocol_fee: U128::from(accounting.protocol_fee.as_u128()),
+            refer_fee: U128::from(accounting.refer_fee.as_u128()),
+            pending_withdrawal: U128::from(accounting.pending_withdrawal.as_u128()),
+            recorded: U128::from(self.internal_get_global_balance(&token).as_u128()),
+            unowned: U128::from(self.internal_get_unowned_amount(&token, accounting.reconciled_balance).as_u128()),
+            reconciled_balance: U128::from(accounting.reconciled_balance.as_u128()),
+            reconcile_time: accounting.reconcile_time,
+        };
+    }
+
+    pub fn query_multi_token(&self, token: AccountId) -> Option<MultiToken> {
+        return self.multi_token_map.get(&token);
+    }
+
+    pub fn query_pending_withdrawals(&self, user: AccountId) -> Vec<PendingWithdrawal> {
+        return self.pending_withdrawals_map.get(&user).unwrap_or_default();
+    }
+
     pub fn query_user_locked_balance(&self, user: AccountId, token: AccountId) -> U128 {
         require!(self.user_locked_balances_map.contains_key(&user), INVALID_USER);
         return U128::from(self.internal_get_user_locked_balance(&user, &token).as_u128());
@@ -43,10 +107,37 @@ impl GridBotContract {
         return self.pair_map.get(&pair_id).unwrap().clone();
     }
 
+    /// latest and twap price of price cache, None if not cached or expired
+    pub fn query_pair_price(&self, pair_id: String) -> Option<PairPrice> {
+        require!(self.pair_map.contains_key(&pair_id), INVALID_PAIR_ID);
+        let pair = self.pair_map.get(&pair_id).unwrap();
+        let (base_price, quote_price) = self.internal_get_cached_prices(&pair, false)?;
+        return Some(PairPrice {
+            price: U128::from(self.internal_get_cached_pair_price(&pair, false)?),
+            twap_price: U128::from(self.internal_get_cached_pair_price(&pair, true)?),
+            update_time: std::cmp::min(base_price.publish_time, quote_price.publish_time) as u64 * 1000,
+        });
+    }
+
+    /// from: index start from 0, in registered order
+    pub fn query_pairs(&self, from: u64, limit: u64) -> Vec<PairOutput> {
+        return self.pair_ids.iter().skip(from as usize).take(limit as usize)
+            .map(|pair_id| self.internal_get_pair_output(&pair_id, &self.pair_map.get(&pair_id).unwrap()))
+            .collect();
+    }
+
+    pub fn query_pair_count(&self) -> u64 {
+        return self.pair_ids.len();
+    }
+
     pub fn query_protocol_fee_rate(&self) -> U128 {
         return U128::from(self.protocol_fee_rate.clone());
     }
 
+    pub fn query_expired_close_reward_rate(&self) -> U128 {
+        return U128::from(self.expired_close_reward_rate.clone());
+    }
+
     pub fn query_market_user(&self, user: AccountId) -> bool {
         if !self.market_user_map.contains_key(&user) {
             return false;
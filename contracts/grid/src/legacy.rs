@@ -0,0 +1,141 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, Balance};
+use crate::*;
+
+/// prefix of bot_map before orderbook, bots are moved out by migrate_bots
+pub const LEGACY_BOTS_PREFIX: &[u8] = b"bots";
+/// prefix of pair_map before orderbook, pairs are moved out by migrate_pairs
+pub const LEGACY_PAIRS_PREFIX: &[u8] = b"pairs";
+
+/// GridBotContract before orderbook, only read by migrate
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldGridBotContract {
+    pub owner_id: AccountId,
+    pub oracle: AccountId,
+    pub oracle_valid_time: u64,
+    pub status: GridStatus,
+    pub protocol_fee_rate: u128,
+    pub taker_fee_rate: u128,
+    pub bot_map: LookupMap<String, OldGridBot>,
+    pub order_map: LookupMap<String, OrdersStorage>,
+    pub next_bot_id: u128,
+    pub pair_map: LookupMap<String, OldPair>,
+    pub protocol_fee_map: LookupMap<AccountId, U256C>,
+    pub global_balances_map: LookupMap<AccountId, U256C>,
+    pub deposit_limit_map: LookupMap<AccountId, U256C>,
+    pub user_balances_map: LookupMap<AccountId, LookupMap<AccountId, U256C>>,
+    pub user_locked_balances_map: LookupMap<AccountId, LookupMap<AccountId, U256C>>,
+    pub market_user_map: LookupMap<AccountId, bool>,
+    pub wnear: AccountId,
+    pub operator_id: AccountId,
+    pub refer_recommender_user_map: LookupMap<AccountId, Vector<AccountId>>,
+    pub refer_user_recommender_map: LookupMap<AccountId, AccountId>,
+    pub refer_fee_map: LookupMap<AccountId, LookupMap<AccountId, U128>>,
+    pub refer_fee_rate: Vec<u32>,
+    pub base_create_storage_fee: Balance,
+    pub storage_price_per_byte: Balance,
+    pub per_grid_storage_fee: Balance,
+}
+
+/// GridBot before orderbook, stored with LEGACY_BOTS_PREFIX
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldGridBot {
+    pub name: String,
+    pub active: bool,
+    pub user: AccountId,
+    pub bot_id: String,
+    pub closed: bool,
+    pub pair_id: String,
+    pub grid_type: GridType,
+    pub grid_sell_count: u16,
+    pub grid_buy_count: u16,
+    pub grid_rate: u16,
+    pub grid_offset: U256C,
+    pub first_base_amount: U256C,
+    pub first_quote_amount: U256C,
+    pub last_base_amount: U256C,
+    pub last_quote_amount: U256C,
+    pub fill_base_or_quote: bool,
+    pub trigger_price: U256C,
+    pub trigger_price_above_or_below: bool,
+    pub take_profit_price: U256C,
+    pub stop_loss_price: U256C,
+    pub valid_until_time: U256C,
+    pub total_quote_amount: U256C,
+    pub total_base_amount: U256C,
+    pub revenue: U256C,
+    pub total_revenue: U256C,
+}
+
+impl OldGridBot {
+    /// storage fee of old bots was attached, so storage_used_bytes is 0 and storage is refunded to user when closed
+    pub fn to_grid_bot(self) -> GridBot {
+        GridBot {
+            name: self.name,
+            active: self.active,
+            user: self.user,
+            bot_id: self.bot_id,
+            closed: self.closed,
+            paused: false,
+            trailing: false,
+            pair_id: self.pair_id,
+            grid_type: self.grid_type,
+            grid_sell_count: self.grid_sell_count,
+            grid_buy_count: self.grid_buy_count,
+            grid_rate: self.grid_rate,
+            grid_offset: self.grid_offset,
+            first_base_amount: self.first_base_amount,
+            first_quote_amount: self.first_quote_amount,
+            last_base_amount: self.last_base_amount,
+            last_quote_amount: self.last_quote_amount,
+            fill_base_or_quote: self.fill_base_or_quote,
+            trigger_price: self.trigger_price,
+            trigger_price_above_or_below: self.trigger_price_above_or_below,
+            take_profit_price: self.take_profit_price,
+            stop_loss_price: self.stop_loss_price,
+            trailing_stop_rate: 0,
+            high_water_price: U256C::from(0),
+            valid_until_time: self.valid_until_time,
+            total_quote_amount: self.total_quote_amount,
+            total_base_amount: self.total_base_amount,
+            revenue: self.revenue,
+            total_revenue: self.total_revenue,
+            keeper_reward: U256C::from(0),
+            keeper_reward_balance: U256C::from(0),
+            custom_levels: vec![],
+            storage_used_bytes: 0,
+        }
+    }
+}
+
+/// Pair before orderbook, stored with LEGACY_PAIRS_PREFIX
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldPair {
+    pub base_token: AccountId,
+    pub quote_token: AccountId,
+    pub base_oracle_id: Option<PriceIdentifier>,
+    pub quote_oracle_id: Option<PriceIdentifier>,
+    pub require_oracle: bool,
+}
+
+impl OldPair {
+    /// token decimals are not known, owner sets them by set_pair_token_decimals
+    pub fn to_pair(self) -> Pair {
+        Pair {
+            base_token: self.base_token,
+            quote_token: self.quote_token,
+            base_oracle_id: self.base_oracle_id,
+            quote_oracle_id: self.quote_oracle_id,
+            require_oracle: self.require_oracle,
+            max_conf_rate: 0,
+            oracle_provider: OracleProvider::Pyth,
+            max_deviation_rate: 0,
+            use_cached_price: false,
+            base_token_decimals: None,
+            quote_token_decimals: None,
+            halt_on_stale_price: false,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 #[allow(unused_imports)]
 use near_sdk::{env, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue};
-use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::collections::{LookupMap, TreeMap, Vector};
 use near_sdk::json_types::U128;
 
 mod utils;
@@ -24,6 +24,9 @@ mod owner;
 mod oracle;
 mod wnear;
 mod grid_bot_check;
+mod storage;
+mod multi_token;
+mod legacy;
 
 pub use crate::constants::*;
 pub use crate::errors::*;
@@ -39,6 +42,8 @@ pub use crate::oracle::*;
 pub struct GridBotContract {
     pub owner_id: AccountId,
     pub oracle: AccountId,
+    /// priceoracle contract, fallback of pyth or primary provider of pair
+    pub price_oracle: Option<AccountId>,
     pub oracle_valid_time: u64,
     pub status: GridStatus,
     /// real_protocol_fee = protocol_fee / 1000000
@@ -53,6 +58,8 @@ pub struct GridBotContract {
     pub next_bot_id: u128,
     /// pair_map[base_token_addr+":"+quote_token_addr] = Pair
     pub pair_map: LookupMap<String, Pair>,
+    /// all pair_id of pair_map, in registered order
+    pub pair_ids: Vector<String>,
     pub protocol_fee_map: LookupMap<AccountId, U256C>,
     // pub storage_fee: u128,
     pub global_balances_map: LookupMap<AccountId, U256C>,
@@ -74,6 +81,26 @@ pub struct GridBotContract {
     pub base_create_storage_fee: Balance,
     pub storage_price_per_byte: Balance,
     pub per_grid_storage_fee: Balance,
+    /// real_expired_close_reward_rate = expired_close_reward_rate / 1000000, the share of storage refund paid to close_expired_bot caller
+    pub expired_close_reward_rate: u128,
+    /// orderbook_map[pair_id:token_sell] = placed orders and the best virtual order of every bot
+    pub orderbook_map: LookupMap<String, TreeMap<BookOrderKey, ()>>,
+    /// user_bots_map[user] = all bots of user, include closed bots
+    pub user_bots_map: LookupMap<AccountId, Vector<String>>,
+    /// user_open_bots_map[user][bot sequence] = not closed bot_id of user, in created order
+    pub user_open_bots_map: LookupMap<AccountId, TreeMap<u128, String>>,
+    /// pair_bots_map[pair_id][bot sequence] = not closed bot_id of pair, in created order
+    pub pair_bots_map: LookupMap<String, TreeMap<u128, String>>,
+    /// price_cache_map[price_identifier] = latest oracle prices, updated by refresh_prices
+    pub price_cache_map: LookupMap<PriceIdentifier, PriceCache>,
+    /// storage_accounts[user] = NEP-145 storage deposit and used bytes
+    pub storage_accounts: LookupMap<AccountId, StorageAccount>,
+    /// pending_withdrawals_map[user] = failed payouts, one per token
+    pub pending_withdrawals_map: LookupMap<AccountId, Vec<PendingWithdrawal>>,
+    /// token_accounting_map[token] = totals of token
+    pub token_accounting_map: LookupMap<AccountId, TokenAccounting>,
+    /// multi_token_map[token key] = NEP-245 token, token key is used as token in pairs and balances
+    pub multi_token_map: LookupMap<AccountId, MultiToken>,
 }
 
 #[near_bindgen]
@@ -84,15 +111,17 @@ impl GridBotContract {
         GridBotContract {
             owner_id: owner_id.clone(),
             oracle,
+            price_oracle: None,
             oracle_valid_time: DEFAULT_ORACLE_VALID_TIME,
             status: GridStatus::Running,
             // 1%
             protocol_fee_rate: DEFAULT_PROTOCOL_FEE,
             taker_fee_rate: DEFAULT_TAKER_FEE,
-            bot_map: LookupMap::new(b"bots".to_vec()),
+            bot_map: LookupMap::new(StorageKey::Bots),
             order_map: LookupMap::new(b"orders_storage".to_vec()),
             next_bot_id: 1,
-            pair_map: LookupMap::new(b"pairs".to_vec()),
+            pair_map: LookupMap::new(StorageKey::Pairs),
+            pair_ids: Vector::new(StorageKey::PairIds),
             protocol_fee_map: LookupMap::new(b"protocol".to_vec()),
             // storage_fee: 0,
             global_balances_map: LookupMap::new(b"global".to_vec()),
@@ -109,6 +138,16 @@ impl GridBotContract {
             base_create_storage_fee: BASE_CREATE_STORAGE_FEE,
             storage_price_per_byte: STORAGE_PRICE_PER_BYTE,
             per_grid_storage_fee: PER_GRID_STORAGE_FEE,
+            expired_close_reward_rate: 0,
+            orderbook_map: LookupMap::new(StorageKey::OrderbookMainKey),
+            user_bots_map: LookupMap::new(StorageKey::UserBotsMainKey),
+            user_open_bots_map: LookupMap::new(StorageKey::UserOpenBotsMainKey),
+            pair_bots_map: LookupMap::new(StorageKey::PairBotsMainKey),
+            price_cache_map: LookupMap::new(StorageKey::PriceCacheMainKey),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccountMainKey),
+            pending_withdrawals_map: LookupMap::new(StorageKey::PendingWithdrawalMainKey),
+            token_accounting_map: LookupMap::new(StorageKey::TokenAccountingMainKey),
+            multi_token_map: LookupMap::new(StorageKey::MultiTokenMainKey),
         }
     }
 }
@@ -0,0 +1,53 @@
+use near_sdk::{env, ext_contract, log, near_bindgen, require, AccountId, PromiseOrValue};
+use near_sdk::json_types::U128;
+use crate::*;
+
+#[ext_contract(ext_multi_token)]
+pub trait MultiTokenCore {
+    fn mt_transfer(&mut self, receiver_id: AccountId, token_id: String, amount: U128, approval: Option<(AccountId, u64)>, memo: Option<String>);
+    fn mt_transfer_call(&mut self, receiver_id: AccountId, token_id: String, amount: U128, approval: Option<(AccountId, u64)>, memo: Option<String>, msg: String) -> PromiseOrValue<Vec<U128>>;
+}
+
+#[near_bindgen]
+impl GridBotContract {
+    /// NEP-245 receiver, same msg as ft_on_transfer, take or create bot request only accept one token,
+    /// return unused amounts, the amounts of not registered tokens are all unused
+    pub fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        let _ = previous_owner_ids;
+        require!(token_ids.len() == amounts.len(), INVALID_PARAM);
+        let contract_id = env::predecessor_account_id();
+        let tokens: Vec<AccountId> = token_ids.iter().map(|token_id| GridBotContract::internal_get_multi_token_key(&contract_id, token_id)).collect();
+        if tokens.iter().any(|token| !self.multi_token_map.contains_key(token)) {
+            log!("{}, contract:{}, token_ids:{:?}", INVALID_TOKEN, contract_id, token_ids);
+            return PromiseOrValue::Value(amounts);
+        }
+        if msg.is_empty() {
+            let unused = tokens.iter().zip(amounts.iter())
+                .map(|(token_in, amount)| if self.internal_deposit(&sender_id, token_in, *amount) { U128::from(0) } else { *amount })
+                .collect();
+            return PromiseOrValue::Value(unused);
+        }
+        require!(tokens.len() == 1, INVALID_PARAM);
+        if let Ok(create_bot_request) = serde_json::from_str::<CreateBotRequest>(&msg) {
+            let unused = self.internal_parse_create_bot_request(&sender_id, &tokens[0], amounts[0], create_bot_request);
+            return PromiseOrValue::Value(vec![unused]);
+        }
+        let left = self.internal_parse_take_request(&sender_id, &tokens[0], amounts[0], msg);
+        return PromiseOrValue::Value(vec![left]);
+    }
+}
+
+impl GridBotContract {
+    /// token key of NEP-245 token, a valid account id which can't be a NEP-141 token of pair
+    pub fn internal_get_multi_token_key(contract_id: &AccountId, token_id: &String) -> AccountId {
+        let hash = env::sha256(format!("{}:{}", contract_id, token_id).as_bytes());
+        return AccountId::new_unchecked(format!("mt-{}", hex::encode(&hash[..20])));
+    }
+}
@@ -1,12 +1,13 @@
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{env, log, near_bindgen, AccountId, PromiseResult, ext_contract, require, Promise};
+use near_sdk::{env, log, near_bindgen, AccountId, PromiseResult, ext_contract, require, Promise, PromiseOrValue};
 // use near_sdk::__private::schemars::schema::SingleOrVec::Vec;
-use near_sdk::json_types::{I64, U64};
+use near_sdk::json_types::{I64, U64, U128};
 use uint::hex;
 use crate::{GAS_FOR_AFTER_ORACLE, GridBot, Pair, U256C};
 use crate::constants::*;
 use crate::errors::*;
+use crate::big_decimal::BigDecimal;
 use crate::*;
 
 #[derive(BorshDeserialize, BorshSerialize, Clone)]
@@ -97,21 +98,107 @@ pub trait Pyth {
     fn get_price(&self, price_identifier: PriceIdentifier) -> Option<Price>;
 }
 
+/// priceoracle price, usd value of 1 smallest token unit = multiplier / 10^decimals
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetPrice {
+    pub multiplier: U128,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetOptionalPrice {
+    pub asset_id: AccountId,
+    pub price: Option<AssetPrice>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceData {
+    /// nanoseconds
+    pub timestamp: U64,
+    pub recency_duration_sec: u32,
+    pub prices: Vec<AssetOptionalPrice>,
+}
+
+#[ext_contract(ext_price_oracle)]
+pub trait PriceOracle {
+    fn get_price_data(&self, asset_ids: Option<Vec<AccountId>>) -> PriceData;
+}
+
+impl OracleProvider {
+    /// promise results count of one pair price request
+    pub fn result_num(&self) -> usize {
+        match self {
+            OracleProvider::Pyth => PAIR_TOKEN_LENGTH,
+            OracleProvider::PriceOracle => 1,
+        }
+    }
+}
+
 impl GridBotContract {
-    fn private_create_pair_price_request(&self, pair: &Pair) -> (Promise, Vec<AccountId>) {
-        let identifiers = vec![pair.base_oracle_id.clone().unwrap(), pair.quote_oracle_id.clone().unwrap()];
+    /// primary provider first, then the fallback one if it's available
+    fn internal_get_oracle_providers(&self, pair: &Pair) -> Vec<OracleProvider> {
+        let mut providers = vec![pair.oracle_provider.clone()];
+        let fallback = if pair.oracle_provider == OracleProvider::Pyth { OracleProvider::PriceOracle } else { OracleProvider::Pyth };
+        let fallback_available = match fallback {
+            OracleProvider::Pyth => pair.base_oracle_id.is_some() && pair.quote_oracle_id.is_some(),
+            OracleProvider::PriceOracle => self.price_oracle.is_some() && GridBotContract::internal_has_token_decimals(pair),
+        };
+        if fallback_available {
+            providers.push(fallback);
+        }
+        return providers;
+    }
+
+    /// request the primary provider, the fallback one is requested by resolve_pair_price only if primary failed
+    /// return (promise, promise_num, tokens), the promise results the price list of pair
+    fn private_create_pair_price_request(&self, pair: &Pair) -> (Promise, usize, Vec<AccountId>) {
         let tokens = vec![pair.base_token.clone(), pair.quote_token.clone()];
-        let mut promise = ext_pyth::ext(self.oracle.clone()).get_price(identifiers[0].clone());
-        for index in 1..identifiers.len() {
-            promise = promise.and(ext_pyth::ext(self.oracle.clone()).get_price(identifiers[index].clone()));
+        let provider = pair.oracle_provider.clone();
+        let promise = self.private_create_provider_price_request(pair, &provider, &tokens)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_PAIR_PRICE)
+                    .resolve_pair_price(provider, tokens.clone(), true),
+            );
+        return (promise, 1, tokens);
+    }
+
+    fn private_create_provider_price_request(&self, pair: &Pair, provider: &OracleProvider, tokens: &Vec<AccountId>) -> Promise {
+        match provider {
+            OracleProvider::Pyth => {
+                ext_pyth::ext(self.oracle.clone()).get_price(pair.base_oracle_id.clone().unwrap())
+                    .and(ext_pyth::ext(self.oracle.clone()).get_price(pair.quote_oracle_id.clone().unwrap()))
+            }
+            OracleProvider::PriceOracle => {
+                ext_price_oracle::ext(self.price_oracle.clone().unwrap()).get_price_data(Some(tokens.clone()))
+            }
         }
-        return (promise, tokens);
     }
 
+    /// price list resolved by resolve_pair_price, empty if all providers failed
     fn private_get_price_list(&self, promise_num: usize, tokens: Vec<AccountId>) -> Vec<Price> {
+        require!(env::promise_results_count() as usize == promise_num, INVALID_PAIR_PRICE_LENGTH);
+        match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                if let Ok(price_list) = near_sdk::serde_json::from_slice::<Vec<Price>>(&value) {
+                    return price_list;
+                }
+                log!(format!("Failure got price list, error deserializing, tokens:{:?}", tokens));
+            }
+            _ => {
+                log!(format!("Failure got price list, tokens:{:?}", tokens));
+            }
+        }
+        return vec![];
+    }
+
+    fn private_get_pyth_price_list(&self, promise_index: usize, tokens: &Vec<AccountId>) -> Vec<Price> {
         let mut price_list = vec![];
-        (0..promise_num).for_each(|index|{
-            let result = env::promise_result(index as u64);
+        (0..PAIR_TOKEN_LENGTH).for_each(|index|{
+            let result = env::promise_result((promise_index + index) as u64);
             match result {
                 PromiseResult::Failed => {
                     log!(format!("Failure got price, token:{}", tokens[index]));
@@ -140,6 +227,160 @@ impl GridBotContract {
         return price_list;
     }
 
+    /// priceoracle price is converted to pyth format of whole token, price = multiplier, expo = token_decimals - decimals, no conf
+    fn private_get_price_oracle_price_list(&self, promise_index: usize, tokens: &Vec<AccountId>, pair: &Pair) -> Vec<Price> {
+        let mut price_list = vec![];
+        if !GridBotContract::internal_has_token_decimals(pair) {
+            log!("Failure got price data, token decimals not set");
+            return price_list;
+        }
+        let price_data = match env::promise_result(promise_index as u64) {
+            PromiseResult::Successful(value) => {
+                if let Ok(price_data) = near_sdk::serde_json::from_slice::<PriceData>(&value) {
+                    price_data
+                } else {
+                    log!("Failure got price data, error deserializing");
+                    return price_list;
+                }
+            }
+            _ => {
+                log!("Failure got price data");
+                return price_list;
+            }
+        };
+        let publish_time = (price_data.timestamp.0 / 1_000_000_000) as i64;
+        let token_decimals = [pair.base_token_decimals.unwrap(), pair.quote_token_decimals.unwrap()];
+        for (index, token) in tokens.iter().enumerate() {
+            let asset_price_op = price_data.prices.iter().find(|asset| &asset.asset_id == token).and_then(|asset| asset.price.clone());
+            if asset_price_op.is_none() {
+                log!(format!("Failure got price, price empty, token:{}", token));
+                return vec![];
+            }
+            let asset_price = asset_price_op.unwrap();
+            if asset_price.multiplier.0 > i64::MAX as u128 {
+                log!(format!("Failure got price, price overflow, token:{}", token));
+                return vec![];
+            }
+            price_list.push(Price {
+                price: I64(asset_price.multiplier.0 as i64),
+                conf: U64(0),
+                expo: token_decimals[index] as i32 - asset_price.decimals as i32,
+                publish_time,
+            });
+        }
+        return price_list;
+    }
+
+    /// oracle_pair_price = quote amount / base amount = (base_price * 10^base_expo) / (quote_price * 10^quote_expo)
+    /// None if price is not positive or conf is too wide
+    pub fn internal_get_oracle_pair_price(pair: &Pair, base_price: &Price, quote_price: &Price) -> Option<u128> {
+        if !GridBotContract::internal_check_price_conf(pair, base_price) || !GridBotContract::internal_check_price_conf(pair, quote_price) {
+            return None;
+        }
+        let mut numerator = BigDecimal::from(base_price.price.0 as u64) * BigDecimal::from(PRICE_DENOMINATOR);
+        let mut denominator = BigDecimal::from(quote_price.price.0 as u64);
+        let expo_diff = base_price.expo as i64 - quote_price.expo as i64;
+        if expo_diff >= 0 {
+            numerator = numerator * BigDecimal::from(10 as u64).pow(expo_diff as u64);
+        } else {
+            denominator = denominator * BigDecimal::from(10 as u64).pow((-expo_diff) as u64);
+        }
+        return Some((numerator / denominator).round_down_u128());
+    }
+
+    pub fn internal_has_token_decimals(pair: &Pair) -> bool {
+        return pair.base_token_decimals.is_some() && pair.quote_token_decimals.is_some();
+    }
+
+    /// oracle pair price is of whole token, book price = oracle_pair_price * 10^quote_token_decimals / 10^base_token_decimals
+    /// None if token decimals of pair are not set
+    pub fn internal_get_oracle_book_price(pair: &Pair, oracle_pair_price: u128) -> Option<u128> {
+        if !GridBotContract::internal_has_token_decimals(pair) {
+            return None;
+        }
+        let (base_token_decimals, quote_token_decimals) = (pair.base_token_decimals.unwrap(), pair.quote_token_decimals.unwrap());
+        let mut numerator = BigDecimal::from(oracle_pair_price);
+        let mut denominator = BigDecimal::from(1 as u64);
+        if quote_token_decimals >= base_token_decimals {
+            numerator = numerator * BigDecimal::from(10 as u64).pow((quote_token_decimals - base_token_decimals) as u64);
+        } else {
+            denominator = denominator * BigDecimal::from(10 as u64).pow((base_token_decimals - quote_token_decimals) as u64);
+        }
+        return Some((numerator / denominator).round_down_u128());
+    }
+
+    /// skip the observation not newer than the last one, restart the ring if expo changed
+    pub fn internal_record_price(&mut self, price_identifier: &PriceIdentifier, price: &Price) {
+        let mut price_cache = self.price_cache_map.get(price_identifier).unwrap_or(PriceCache { observations: vec![] });
+        if let Some(last_price) = price_cache.observations.last() {
+            if price.publish_time <= last_price.publish_time {
+                return;
+            }
+            if price.expo != last_price.expo {
+                price_cache.observations.clear();
+            }
+        }
+        price_cache.observations.push(price.clone());
+        if price_cache.observations.len() > MAX_PRICE_OBSERVATIONS {
+            price_cache.observations.remove(0);
+        }
+        self.price_cache_map.insert(price_identifier, &price_cache);
+    }
+
+    /// every observation is weighted by the time until next one, the last one until now
+    pub fn internal_get_twap_price(observations: &Vec<Price>, now_sec: i64) -> Price {
+        let last_price = observations.last().unwrap();
+        let mut weighted_sum: i128 = 0;
+        let mut total_time: i128 = 0;
+        for (index, observation) in observations.iter().enumerate() {
+            let end_time = if index + 1 < observations.len() { observations[index + 1].publish_time } else { std::cmp::max(now_sec, last_price.publish_time) };
+            let duration = (end_time - observation.publish_time) as i128;
+            weighted_sum += observation.price.0 as i128 * duration;
+            total_time += duration;
+        }
+        let twap = if total_time == 0 { last_price.price.0 } else { (weighted_sum / total_time) as i64 };
+        return Price {
+            price: I64(twap),
+            conf: last_price.conf.clone(),
+            expo: last_price.expo,
+            publish_time: last_price.publish_time,
+        };
+    }
+
+    /// cached (base_price, quote_price), None if not cached or expired
+    pub fn internal_get_cached_prices(&self, pair: &Pair, twap: bool) -> Option<(Price, Price)> {
+        let mut prices = vec![];
+        for price_identifier in [pair.base_oracle_id.as_ref(), pair.quote_oracle_id.as_ref()] {
+            let price_cache = self.price_cache_map.get(price_identifier?)?;
+            let last_price = price_cache.observations.last()?;
+            if last_price.publish_time as u64 * 1000 + self.oracle_valid_time.clone() < env::block_timestamp_ms() {
+                return None;
+            }
+            if twap {
+                prices.push(GridBotContract::internal_get_twap_price(&price_cache.observations, (env::block_timestamp_ms() / 1000) as i64));
+            } else {
+                prices.push(last_price.clone());
+            }
+        }
+        return Some((prices[0].clone(), prices[1].clone()));
+    }
+
+    pub fn internal_get_cached_pair_price(&self, pair: &Pair, twap: bool) -> Option<u128> {
+        let (base_price, quote_price) = self.internal_get_cached_prices(pair, twap)?;
+        return GridBotContract::internal_get_oracle_pair_price(pair, &base_price, &quote_price);
+    }
+
+    /// conf / price <= max_conf_rate / CONF_RATE_DENOMINATOR
+    fn internal_check_price_conf(pair: &Pair, price: &Price) -> bool {
+        if price.price.0 <= 0 {
+            return false;
+        }
+        if pair.max_conf_rate == 0 {
+            return true;
+        }
+        return price.conf.0 as u128 * CONF_RATE_DENOMINATOR as u128 <= price.price.0 as u128 * pair.max_conf_rate as u128;
+    }
+
     pub fn get_price_for_create_bot(
         &mut self,
         pair: &Pair,
@@ -148,13 +389,14 @@ impl GridBotContract {
         entry_price: &U256C,
         grid_bot: &mut GridBot,
         recommender: Option<AccountId>,
-        storage_fee: u128
+        storage_fee: u128,
+        storage_drawn: u128
     ) {
-        let (promise, tokens) = self.private_create_pair_price_request(pair);
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
         promise.then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_CREATE_BOT_AFTER_ORACLE)
-                .get_price_for_create_bot_callback(tokens.len(), tokens, user, slippage, entry_price, pair, grid_bot, recommender, storage_fee),
+                .get_price_for_create_bot_callback(promise_num, tokens, user, slippage, entry_price, pair, grid_bot, recommender, storage_fee, storage_drawn),
         );
     }
 
@@ -162,26 +404,65 @@ impl GridBotContract {
         &mut self,
         user: &AccountId,
         pair: &Pair,
-        grid_bot: &mut GridBot,
+        bot_id: String,
+    ) {
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
+        promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_AFTER_ORACLE)
+                .get_price_for_close_bot_callback(promise_num, tokens, user, bot_id),
+        );
+    }
+
+    pub fn get_price_for_trail_bot(
+        &mut self,
+        pair: &Pair,
+        bot_id: String,
     ) {
-        let (promise, tokens) = self.private_create_pair_price_request(pair);
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
+        promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRAIL_BOT_AFTER_ORACLE)
+                .get_price_for_trail_bot_callback(promise_num, tokens, bot_id),
+        );
+    }
+
+    pub fn get_price_for_high_water_price(
+        &mut self,
+        pair: &Pair,
+        bot_id: String,
+    ) {
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
         promise.then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_ORACLE)
-                .get_price_for_close_bot_callback(tokens.len(), tokens, user, pair, grid_bot),
+                .get_price_for_high_water_price_callback(promise_num, tokens, bot_id),
+        );
+    }
+
+    pub fn get_price_for_refresh_prices(
+        &mut self,
+        pair: &Pair,
+    ) {
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
+        promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_AFTER_ORACLE)
+                .get_price_for_refresh_prices_callback(promise_num, tokens),
         );
     }
 
     pub fn get_price_for_trigger_bot(
         &mut self,
         pair: &Pair,
-        grid_bot: &mut GridBot,
+        bot_id: String,
+        keeper: &AccountId,
     ) {
-        let (promise, tokens) = self.private_create_pair_price_request(pair);
+        let (promise, promise_num, tokens) = self.private_create_pair_price_request(pair);
         promise.then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_ORACLE)
-                .get_price_for_trigger_bot_callback(tokens.len(), tokens, grid_bot),
+                .get_price_for_trigger_bot_callback(promise_num, tokens, bot_id, keeper),
         );
     }
 }
@@ -190,9 +471,13 @@ impl GridBotContract {
 trait ExtSelf {
     fn get_price_for_create_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, user: &AccountId,
                                          slippage: u16, entry_price: &U256C, pair: &Pair, grid_bot: &mut GridBot,
-                                         recommender: Option<AccountId>, storage_fee: u128) -> bool;
-    fn get_price_for_close_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, user: &AccountId, pair: &Pair, grid_bot: &mut GridBot);
-    fn get_price_for_trigger_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, grid_bot: &mut GridBot);
+                                         recommender: Option<AccountId>, storage_fee: u128, storage_drawn: u128) -> bool;
+    fn get_price_for_close_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, user: &AccountId, bot_id: String);
+    fn get_price_for_trigger_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String, keeper: &AccountId);
+    fn get_price_for_trail_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String);
+    fn get_price_for_high_water_price_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String);
+    fn get_price_for_refresh_prices_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>);
+    fn resolve_pair_price(&mut self, provider: OracleProvider, tokens: Vec<AccountId>, try_fallback: bool) -> PromiseOrValue<Vec<Price>>;
 }
 
 #[near_bindgen]
@@ -201,27 +486,89 @@ impl ExtSelf for GridBotContract {
     fn get_price_for_create_bot_callback(&mut self,
                                          promise_num: usize, tokens: Vec<AccountId>, user: &AccountId,
                                          slippage: u16, entry_price: &U256C, pair: &Pair, grid_bot: &mut GridBot,
-                                         recommender: Option<AccountId>, storage_fee: u128
+                                         recommender: Option<AccountId>, storage_fee: u128, storage_drawn: u128
     ) -> bool {
         let price_list = self.private_get_price_list(promise_num, tokens);
         if price_list.len() != PAIR_TOKEN_LENGTH {
-            self.internal_create_bot_refund_with_near(user, pair, storage_fee, INVALID_PAIR_PRICE_LENGTH);
+            self.internal_create_bot_refund_with_storage(user, pair, storage_fee, storage_drawn, INVALID_PAIR_PRICE_LENGTH);
             return false;
         }
-        return self.internal_create_bot(Some(price_list[0].clone()), Some(price_list[1].clone()), user, slippage, entry_price, pair, recommender, storage_fee, grid_bot);
+        return self.internal_create_bot(Some(price_list[0].clone()), Some(price_list[1].clone()), user, slippage, entry_price, pair, recommender, storage_fee, storage_drawn, grid_bot);
     }
 
     #[private]
-    fn get_price_for_close_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, user:&AccountId, pair: &Pair, grid_bot: &mut GridBot) {
+    fn get_price_for_close_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, user:&AccountId, bot_id: String) {
         let price_list = self.private_get_price_list(promise_num, tokens);
         require!(price_list.len() == PAIR_TOKEN_LENGTH, INVALID_PAIR_PRICE_LENGTH);
-        self.internal_auto_close_bot(price_list[0].clone(), price_list[1].clone(), user, &grid_bot.bot_id.clone(), grid_bot, pair);
+        // bot may be closed while requesting price, reload it
+        let mut bot = self.bot_map.get(&bot_id).unwrap();
+        require!(!bot.closed, INVALID_BOT_STATUS);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap();
+        self.internal_auto_close_bot(price_list[0].clone(), price_list[1].clone(), user, &bot_id, &mut bot, &pair);
     }
 
     #[private]
-    fn get_price_for_trigger_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, grid_bot: &mut GridBot) {
+    fn get_price_for_trigger_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String, keeper: &AccountId) {
         let price_list = self.private_get_price_list(promise_num, tokens);
         require!(price_list.len() == PAIR_TOKEN_LENGTH, INVALID_PAIR_PRICE_LENGTH);
-        self.internal_trigger_bot(price_list[0].clone(), price_list[1].clone(), &grid_bot.bot_id.clone(), grid_bot);
+        // bot may be triggered or closed while requesting price, reload it
+        let mut bot = self.bot_map.get(&bot_id).unwrap();
+        self.internal_trigger_bot(price_list[0].clone(), price_list[1].clone(), keeper, &bot_id, &mut bot);
+    }
+
+    #[private]
+    fn get_price_for_trail_bot_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String) {
+        let price_list = self.private_get_price_list(promise_num, tokens);
+        require!(price_list.len() == PAIR_TOKEN_LENGTH, INVALID_PAIR_PRICE_LENGTH);
+        // bot may be taken or changed while requesting price, reload it
+        let mut bot = self.bot_map.get(&bot_id).unwrap();
+        require!(bot.trailing && self.internal_check_bot_can_take(&bot), INVALID_BOT_STATUS);
+        let pair = self.pair_map.get(&bot.pair_id).unwrap();
+        self.internal_trail_bot(price_list[0].clone(), price_list[1].clone(), &bot_id, &mut bot, &pair);
+    }
+
+    #[private]
+    fn get_price_for_high_water_price_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>, bot_id: String) {
+        let price_list = self.private_get_price_list(promise_num, tokens);
+        require!(price_list.len() == PAIR_TOKEN_LENGTH, INVALID_PAIR_PRICE_LENGTH);
+        let mut bot = self.bot_map.get(&bot_id).unwrap();
+        require!(!bot.closed && bot.trailing_stop_rate > 0, INVALID_BOT_STATUS);
+        self.internal_update_high_water_price(price_list[0].clone(), price_list[1].clone(), &bot_id, &mut bot);
+    }
+
+    #[private]
+    fn get_price_for_refresh_prices_callback(&mut self, promise_num: usize, tokens: Vec<AccountId>) {
+        let price_list = self.private_get_price_list(promise_num, tokens.clone());
+        require!(price_list.len() == PAIR_TOKEN_LENGTH, INVALID_PAIR_PRICE_LENGTH);
+        let pair_id = GridBotContract::internal_get_pair_key(tokens[0].clone(), tokens[1].clone());
+        let pair = self.pair_map.get(&pair_id).unwrap();
+        self.internal_refresh_prices(price_list[0].clone(), price_list[1].clone(), &pair_id, &pair);
+    }
+
+    /// price list of provider, request the fallback provider if it failed and try_fallback
+    #[private]
+    fn resolve_pair_price(&mut self, provider: OracleProvider, tokens: Vec<AccountId>, try_fallback: bool) -> PromiseOrValue<Vec<Price>> {
+        let pair = self.pair_map.get(&GridBotContract::internal_get_pair_key(tokens[0].clone(), tokens[1].clone())).unwrap();
+        let price_list = match provider {
+            OracleProvider::Pyth => self.private_get_pyth_price_list(0, &tokens),
+            OracleProvider::PriceOracle => self.private_get_price_oracle_price_list(0, &tokens, &pair),
+        };
+        if price_list.len() == PAIR_TOKEN_LENGTH || !try_fallback {
+            return PromiseOrValue::Value(price_list);
+        }
+        log!(format!("Failure got price from {:?}", provider));
+        let providers = self.internal_get_oracle_providers(&pair);
+        if providers.len() < 2 {
+            return PromiseOrValue::Value(vec![]);
+        }
+        let fallback = providers[1].clone();
+        return PromiseOrValue::Promise(
+            self.private_create_provider_price_request(&pair, &fallback, &tokens)
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_AFTER_ORACLE)
+                        .resolve_pair_price(fallback, tokens, false),
+                )
+        );
     }
 }
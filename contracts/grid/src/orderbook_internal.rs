@@ -1,14 +1,19 @@
+use std::collections::BTreeMap;
 use std::ops::{Div, Mul};
 use near_sdk::{require};
+use near_sdk::collections::{TreeMap, Vector};
+use near_sdk::json_types::U128;
 use crate::*;
 use crate::big_decimal::BigDecimal;
-use crate::entity::GridType::EqOffset;
+use crate::entity::GridType::{Custom, EqOffset};
 use crate::events::emit;
 
 impl GridBotContract {
     pub fn internal_place_order(&mut self, bot_id: String, order: Order, forward_or_reverse: bool, level: usize) {
         require!(self.bot_map.contains_key(&bot_id), INVALID_BOT_ID_FOR_BOT_MAP);
         require!(self.order_map.contains_key(&bot_id), INVALID_BOT_ID_FOR_ORDER_MAP);
+        let bot = self.bot_map.get(&bot_id).unwrap();
+        let pair = self.pair_map.get(&bot.pair_id).unwrap();
 
         let mut bot_orders = self.order_map.get(&bot_id).unwrap();
         let orders = if forward_or_reverse {
@@ -19,11 +24,18 @@ impl GridBotContract {
         let placed_order = &mut orders.get(level.clone() as u64).unwrap();
         if GridBotContract::internal_order_is_empty(placed_order) {
             orders.replace(level.clone() as u64, &order);
+            if forward_or_reverse {
+                // the virtual order is placed, move the bot's best virtual order to next level
+                self.internal_move_virtual_order(&bot, &pair, &bot_orders.forward_orders, level.clone());
+            }
+            self.internal_insert_book_order(&pair, &bot_id, &order, forward_or_reverse.clone(), level.clone());
         } else {
+            self.internal_remove_book_order(&pair, &bot_id, placed_order, forward_or_reverse.clone(), level.clone());
             // merge order
             placed_order.amount_sell += order.amount_sell;
             placed_order.amount_buy += order.amount_buy;
             orders.replace(level.clone() as u64, placed_order);
+            self.internal_insert_book_order(&pair, &bot_id, placed_order, forward_or_reverse.clone(), level.clone());
         }
 
         self.order_map.insert(&bot_id, &bot_orders);
@@ -35,6 +47,8 @@ impl GridBotContract {
         let (maker_order, in_orderbook) = self.query_order(bot_id.clone(), forward_or_reverse, level);
         // matching check
         GridBotContract::internal_check_order_match(maker_order.clone(), taker_order.clone());
+        // circuit breaker
+        self.internal_check_take_price_deviation(&maker_order, &pair);
 
         // calculate
         let (taker_sell, taker_buy, current_filled, made_order) = GridBotContract::internal_calculate_matching(maker_order.clone(), taker_order.clone(), took_sell, took_buy);
@@ -91,6 +105,13 @@ impl GridBotContract {
 
         // bot_orders.replace(index, &orders);
         self.order_map.insert(&bot_id, &bot_orders);
+
+        // filled order can't be taken anymore
+        if GridBotContract::internal_order_is_filled(order) {
+            let pair_id = self.bot_map.get(&bot_id).unwrap().pair_id;
+            let pair = self.pair_map.get(&pair_id).unwrap();
+            self.internal_remove_book_order(&pair, &bot_id, order, forward_or_reverse, level);
+        }
         return order.clone();
     }
 
@@ -105,16 +126,7 @@ impl GridBotContract {
 
     pub fn internal_calculate_matching(maker_order: Order, taker_order: Order, took_sell: U256C, took_buy: U256C) -> (U256C, U256C, U256C, Order) {
         // calculate marker max amount
-        let max_fill_sell;
-        let max_fill_buy;
-        if maker_order.fill_buy_or_sell {
-            max_fill_buy = maker_order.amount_buy - maker_order.filled;
-            max_fill_sell = maker_order.amount_sell * max_fill_buy / maker_order.amount_buy;
-        } else {
-            max_fill_sell = maker_order.amount_sell - maker_order.filled;
-            // max_fill_buy = maker_order.amount_buy * max_fill_sell / maker_order.amount_sell;
-            max_fill_buy = U256C::from(BigDecimal::from(maker_order.amount_buy.as_u128()).mul(BigDecimal::from(max_fill_sell.as_u128())).div(BigDecimal::from(maker_order.amount_sell.as_u128())).round_up_u128());
-        }
+        let (max_fill_sell, max_fill_buy) = GridBotContract::internal_get_order_remaining(&maker_order);
         // calculate matching amount
         let taker_sell;
         let taker_buy;
@@ -153,10 +165,43 @@ impl GridBotContract {
         return (taker_sell, taker_buy, current_filled, made_order);
     }
 
+    /// return (remaining sell, remaining buy)
+    pub fn internal_get_order_remaining(order: &Order) -> (U256C, U256C) {
+        let remaining_sell;
+        let remaining_buy;
+        if order.fill_buy_or_sell {
+            remaining_buy = order.amount_buy - order.filled;
+            remaining_sell = order.amount_sell * remaining_buy / order.amount_buy;
+        } else {
+            remaining_sell = order.amount_sell - order.filled;
+            // remaining_buy = order.amount_buy * remaining_sell / order.amount_sell;
+            remaining_buy = U256C::from(BigDecimal::from(order.amount_buy.as_u128()).mul(BigDecimal::from(remaining_sell.as_u128())).div(BigDecimal::from(order.amount_sell.as_u128())).round_up_u128());
+        }
+        return (remaining_sell, remaining_buy);
+    }
+
+    pub fn internal_get_order_output(order: &Order, placed: bool, pair: &Pair) -> OrderOutput {
+        let (remaining_sell, remaining_buy) = GridBotContract::internal_get_order_remaining(order);
+        OrderOutput {
+            order: order.to_request_order(),
+            placed,
+            remaining_sell: U128::from(remaining_sell.as_u128()),
+            remaining_buy: U128::from(remaining_buy.as_u128()),
+            price: U128::from(GridBotContract::internal_get_book_price(order, pair)),
+        }
+    }
+
     pub fn internal_order_is_empty(order: &Order) -> bool {
         return order.amount_buy == U256C::from(0) || order.amount_sell == U256C::from(0)
     }
 
+    pub fn internal_order_is_filled(order: &Order) -> bool {
+        if order.fill_buy_or_sell {
+            return order.filled >= order.amount_buy;
+        }
+        return order.filled >= order.amount_sell;
+    }
+
     pub fn internal_get_opposite_order(made_order: &Order, bot: GridBot, forward_or_reverse: bool, level: usize) -> Order {
         let mut reverse_order = Order{
             token_sell: made_order.token_buy.clone(),
@@ -166,6 +211,9 @@ impl GridBotContract {
             fill_buy_or_sell: !made_order.fill_buy_or_sell.clone(),
             filled: U256C::from(0),
         };
+        if bot.grid_type == Custom {
+            return GridBotContract::internal_get_custom_opposite_order(made_order, &bot, forward_or_reverse, level, reverse_order);
+        }
         if made_order.fill_buy_or_sell {
             // reverse_order fill sell, fixed sell
             reverse_order.amount_sell = made_order.amount_buy.clone();
@@ -226,6 +274,34 @@ impl GridBotContract {
         return reverse_order;
     }
 
+    /// forward made: reverse order at the price of neighbour level, buy grid => level + 1, sell grid => level - 1
+    /// reverse made: forward order back to the price of current level
+    pub fn internal_get_custom_opposite_order(made_order: &Order, bot: &GridBot, forward_or_reverse: bool, level: usize, mut opposite_order: Order) -> Order {
+        let is_buy_level = (bot.grid_buy_count as usize) > level;
+        let price_level = if !forward_or_reverse {
+            level
+        } else if is_buy_level {
+            level + 1
+        } else {
+            level - 1
+        };
+        let custom_level = &bot.custom_levels[price_level];
+        // opposite order sell base when: buy grid and forward made, sell grid and reverse made
+        let (price_sell, price_buy) = if is_buy_level == forward_or_reverse {
+            (custom_level.base_amount, custom_level.quote_amount)
+        } else {
+            (custom_level.quote_amount, custom_level.base_amount)
+        };
+        if made_order.fill_buy_or_sell {
+            opposite_order.amount_sell = made_order.amount_buy.clone();
+            opposite_order.amount_buy = U256C::from(BigDecimal::from(opposite_order.amount_sell.as_u128()).mul(BigDecimal::from(price_buy.as_u128())).div(BigDecimal::from(price_sell.as_u128())).round_up_u128());
+        } else {
+            opposite_order.amount_buy = made_order.amount_sell.clone();
+            opposite_order.amount_sell = opposite_order.amount_buy * price_sell / price_buy;
+        }
+        return opposite_order;
+    }
+
     pub fn internal_calculate_bot_revenue(&self, forward_or_reverse: bool, made_order: Order, opposite_order: Order) -> (AccountId, U256C, U256C) {
         let revenue_token = if opposite_order.fill_buy_or_sell { opposite_order.token_sell } else { opposite_order.token_buy };
         if forward_or_reverse || opposite_order.amount_sell.as_u128() == 0 || opposite_order.amount_buy.as_u128() == 0 {
@@ -249,4 +325,226 @@ impl GridBotContract {
         return (took_buy - taker_fee, taker_fee);
     }
 
+    //################################## Orderbook #################################################
+    pub fn internal_get_orderbook_key(pair_id: &String, token_sell: &AccountId) -> String {
+        return format!("{}:{}", pair_id, token_sell.to_string());
+    }
+
+    /// price = quote amount / base amount * 10^18
+    pub fn internal_get_book_price(order: &Order, pair: &Pair) -> u128 {
+        if order.token_sell == pair.base_token {
+            return (order.amount_buy * U256C::from(PRICE_DENOMINATOR) / order.amount_sell).as_u128();
+        }
+        return (order.amount_sell * U256C::from(PRICE_DENOMINATOR) / order.amount_buy).as_u128();
+    }
+
+    pub fn internal_insert_book_order(&mut self, pair: &Pair, bot_id: &String, order: &Order, forward_or_reverse: bool, level: usize) {
+        if GridBotContract::internal_order_is_empty(order) || GridBotContract::internal_order_is_filled(order) {
+            return;
+        }
+        let pair_id = GridBotContract::internal_get_pair_key(pair.base_token.clone(), pair.quote_token.clone());
+        let orderbook_key = GridBotContract::internal_get_orderbook_key(&pair_id, &order.token_sell);
+        let mut orderbook = self.orderbook_map.get(&orderbook_key).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::OrderbookSubKey(orderbook_key.clone()))
+        });
+        let book_order_key = BookOrderKey {
+            price: GridBotContract::internal_get_book_price(order, pair),
+            bot_id: bot_id.clone(),
+            forward_or_reverse,
+            level,
+        };
+        orderbook.insert(&book_order_key, &());
+        self.orderbook_map.insert(&orderbook_key, &orderbook);
+    }
+
+    /// return true if the order was in orderbook
+    pub fn internal_remove_book_order(&mut self, pair: &Pair, bot_id: &String, order: &Order, forward_or_reverse: bool, level: usize) -> bool {
+        if GridBotContract::internal_order_is_empty(order) {
+            return false;
+        }
+        let pair_id = GridBotContract::internal_get_pair_key(pair.base_token.clone(), pair.quote_token.clone());
+        let orderbook_key = GridBotContract::internal_get_orderbook_key(&pair_id, &order.token_sell);
+        let orderbook_op = self.orderbook_map.get(&orderbook_key);
+        if orderbook_op.is_none() {
+            return false;
+        }
+        let mut orderbook = orderbook_op.unwrap();
+        let book_order_key = BookOrderKey {
+            price: GridBotContract::internal_get_book_price(order, pair),
+            bot_id: bot_id.clone(),
+            forward_or_reverse,
+            level,
+        };
+        if orderbook.remove(&book_order_key).is_none() {
+            return false;
+        }
+        self.orderbook_map.insert(&orderbook_key, &orderbook);
+        return true;
+    }
+
+    /// the not placed forward level with the best price, search from level to the edge of grid
+    /// buy grid: search downward from level to 0, sell grid: search upward from level to the last level
+    pub fn internal_get_best_virtual_level(bot: &GridBot, forward_orders: &Vector<Order>, level: usize) -> Option<usize> {
+        let grid_buy_count = bot.grid_buy_count.clone() as usize;
+        let grid_count = grid_buy_count + bot.grid_sell_count.clone() as usize;
+        if level < grid_buy_count {
+            return (0..(level + 1)).rev().find(|index| GridBotContract::internal_order_is_empty(&forward_orders.get(index.clone() as u64).unwrap()));
+        }
+        return (level..grid_count).find(|index| GridBotContract::internal_order_is_empty(&forward_orders.get(index.clone() as u64).unwrap()));
+    }
+
+    /// only the best virtual order of each side is in orderbook, the others are placed one after another
+    pub fn internal_add_bot_to_orderbook(&mut self, bot: &GridBot, pair: &Pair) {
+        if bot.grid_buy_count > 0 {
+            let level = (bot.grid_buy_count - 1) as usize;
+            let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level);
+            self.internal_insert_book_order(pair, &bot.bot_id, &order, true, level);
+        }
+        if bot.grid_sell_count > 0 {
+            let level = bot.grid_buy_count as usize;
+            let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level);
+            self.internal_insert_book_order(pair, &bot.bot_id, &order, true, level);
+        }
+    }
+
+    pub fn internal_move_virtual_order(&mut self, bot: &GridBot, pair: &Pair, forward_orders: &Vector<Order>, placed_level: usize) {
+        let virtual_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), placed_level.clone());
+        if !self.internal_remove_book_order(pair, &bot.bot_id, &virtual_order, true, placed_level.clone()) {
+            return;
+        }
+        let grid_buy_count = bot.grid_buy_count.clone() as usize;
+        let grid_count = grid_buy_count + bot.grid_sell_count.clone() as usize;
+        let next_level_op = if placed_level < grid_buy_count {
+            if placed_level == 0 { None } else { GridBotContract::internal_get_best_virtual_level(bot, forward_orders, placed_level - 1) }
+        } else {
+            if placed_level + 1 >= grid_count { None } else { GridBotContract::internal_get_best_virtual_level(bot, forward_orders, placed_level + 1) }
+        };
+        if let Some(next_level) = next_level_op {
+            let next_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), next_level.clone());
+            self.internal_insert_book_order(pair, &bot.bot_id, &next_order, true, next_level);
+        }
+    }
+
+    /// the next not placed forward level after the walked virtual order, away from the best price
+    pub fn internal_add_next_virtual_order(&self, virtual_orders: &mut BTreeMap<BookOrderKey, Order>, bot: &GridBot, pair: &Pair, book_order_key: &BookOrderKey) {
+        let level = book_order_key.level;
+        let grid_buy_count = bot.grid_buy_count as usize;
+        let grid_count = grid_buy_count + bot.grid_sell_count as usize;
+        let bot_orders = self.order_map.get(&bot.bot_id).unwrap();
+        let next_level_op = if level < grid_buy_count {
+            if level == 0 { None } else { GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, level - 1) }
+        } else {
+            if level + 1 >= grid_count { None } else { GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, level + 1) }
+        };
+        if let Some(next_level) = next_level_op {
+            let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), next_level);
+            let key = BookOrderKey {
+                price: GridBotContract::internal_get_book_price(&order, pair),
+                bot_id: bot.bot_id.clone(),
+                forward_or_reverse: true,
+                level: next_level,
+            };
+            virtual_orders.insert(key, order);
+        }
+    }
+
+    pub fn internal_remove_bot_from_orderbook(&mut self, bot: &GridBot, pair: &Pair) {
+        let bot_orders = self.order_map.get(&bot.bot_id).unwrap();
+        // virtual orders
+        if bot.grid_buy_count > 0 {
+            if let Some(level) = GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, (bot.grid_buy_count - 1) as usize) {
+                let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level.clone());
+                self.internal_remove_book_order(pair, &bot.bot_id, &order, true, level);
+            }
+        }
+        if bot.grid_sell_count > 0 {
+            if let Some(level) = GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, bot.grid_buy_count as usize) {
+                let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level.clone());
+                self.internal_remove_book_order(pair, &bot.bot_id, &order, true, level);
+            }
+        }
+        // placed orders
+        for (level, order) in bot_orders.forward_orders.iter().enumerate() {
+            if !GridBotContract::internal_order_is_filled(&order) {
+                self.internal_remove_book_order(pair, &bot.bot_id, &order, true, level);
+            }
+        }
+        for (level, order) in bot_orders.reverse_orders.iter().enumerate() {
+            if !GridBotContract::internal_order_is_filled(&order) {
+                self.internal_remove_book_order(pair, &bot.bot_id, &order, false, level);
+            }
+        }
+    }
+
+    /// put back the best virtual orders and the placed orders, reverse of internal_remove_bot_from_orderbook
+    pub fn internal_restore_bot_to_orderbook(&mut self, bot: &GridBot, pair: &Pair) {
+        let bot_orders = self.order_map.get(&bot.bot_id).unwrap();
+        // virtual orders
+        if bot.grid_buy_count > 0 {
+            if let Some(level) = GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, (bot.grid_buy_count - 1) as usize) {
+                let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level.clone());
+                self.internal_insert_book_order(pair, &bot.bot_id, &order, true, level);
+            }
+        }
+        if bot.grid_sell_count > 0 {
+            if let Some(level) = GridBotContract::internal_get_best_virtual_level(bot, &bot_orders.forward_orders, bot.grid_buy_count as usize) {
+                let order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level.clone());
+                self.internal_insert_book_order(pair, &bot.bot_id, &order, true, level);
+            }
+        }
+        // placed orders
+        for (level, order) in bot_orders.forward_orders.iter().enumerate() {
+            self.internal_insert_book_order(pair, &bot.bot_id, &order, true, level);
+        }
+        for (level, order) in bot_orders.reverse_orders.iter().enumerate() {
+            self.internal_insert_book_order(pair, &bot.bot_id, &order, false, level);
+        }
+    }
+
+    /// walk the orderbook from the best price, until taker order is filled or reach taker's price
+    /// at most MAX_BOOK_ITERATIONS book orders are visited, so that not takeable bots can't exhaust gas
+    pub fn internal_get_best_maker_orders(&self, pair_id: &String, taker_order: &Order, max_makers: u16) -> Vec<OrderKeyInfo> {
+        let pair = self.pair_map.get(pair_id).unwrap();
+        require!(taker_order.token_sell == pair.base_token || taker_order.token_sell == pair.quote_token, INVALID_ORDER_TOKEN);
+        let mut maker_orders: Vec<OrderKeyInfo> = vec![];
+        let orderbook_op = self.orderbook_map.get(&GridBotContract::internal_get_orderbook_key(pair_id, &taker_order.token_buy));
+        if orderbook_op.is_none() {
+            return maker_orders;
+        }
+        let orderbook = orderbook_op.unwrap();
+        // sell orders start from the lowest price, buy orders start from the highest price
+        let book_orders: Box<dyn Iterator<Item = (BookOrderKey, ())>> = if taker_order.token_buy == pair.base_token {
+            Box::new(orderbook.iter())
+        } else {
+            Box::new(orderbook.iter_rev())
+        };
+        let mut took_sell = U256C::from(0);
+        let mut took_buy = U256C::from(0);
+        for (book_order_key, _) in book_orders.take(MAX_BOOK_ITERATIONS as usize) {
+            if maker_orders.len() >= max_makers as usize {
+                break;
+            }
+            let bot = self.bot_map.get(&book_order_key.bot_id).unwrap();
+            if !self.internal_check_bot_can_take(&bot) {
+                continue;
+            }
+            let (maker_order, _) = self.query_order(book_order_key.bot_id.clone(), book_order_key.forward_or_reverse.clone(), book_order_key.level.clone());
+            // reach taker's price
+            if taker_order.amount_sell * maker_order.amount_sell < taker_order.amount_buy * maker_order.amount_buy {
+                break;
+            }
+            let (taker_sell, taker_buy, _, _) = GridBotContract::internal_calculate_matching(maker_order, taker_order.clone(), took_sell.clone(), took_buy.clone());
+            took_sell += taker_sell;
+            took_buy += taker_buy;
+            maker_orders.push(OrderKeyInfo {
+                bot_id: book_order_key.bot_id,
+                forward_or_reverse: book_order_key.forward_or_reverse,
+                level: book_order_key.level,
+            });
+            if (taker_order.fill_buy_or_sell && took_buy >= taker_order.amount_buy) || (!taker_order.fill_buy_or_sell && took_sell >= taker_order.amount_sell) {
+                break;
+            }
+        }
+        return maker_orders;
+    }
 }
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use crate::*;
 use near_sdk::{near_bindgen, require};
+use near_sdk::json_types::U128;
 
 #[near_bindgen]
 impl GridBotContract {
@@ -11,6 +13,7 @@ impl GridBotContract {
         let bot = self.bot_map.get(&bot_id).unwrap();
         require!(!(bot.closed.clone()), bot.bot_id.clone() + BOT_CLOSED);
         require!(bot.active.clone(), bot.bot_id.clone() + BOT_DISABLE);
+        require!(!(bot.paused.clone()), bot.bot_id.clone() + BOT_PAUSED);
         require!(self.pair_map.contains_key(&(bot.pair_id.clone())), INVALID_PAIR_ID);
         // check timestamp
         require!(bot.valid_until_time >= U256C::from(env::block_timestamp_ms()), BOT_EXPIRED);
@@ -44,6 +47,117 @@ impl GridBotContract {
         return orders;
     }
 
+    /// all levels of bot, include virtual forward orders, from_level start from 0
+    pub fn query_bot_orders(&self, bot_id: String, from_level: usize, limit: usize) -> Vec<LevelOrdersOutput> {
+        require!(self.order_map.contains_key(&bot_id), INVALID_BOT_ID);
+        require!(self.bot_map.contains_key(&bot_id), INVALID_BOT_ID);
+        let bot = self.bot_map.get(&bot_id).unwrap();
+        let pair = self.pair_map.get(&(bot.pair_id.clone())).unwrap();
+        let bot_orders = self.order_map.get(&bot_id).unwrap();
+        let grid_count = bot_orders.forward_orders.len() as usize;
+        let mut level_orders: Vec<LevelOrdersOutput> = vec![];
+        for level in from_level..grid_count.min(from_level.saturating_add(limit)) {
+            let forward_order = bot_orders.forward_orders.get(level.clone() as u64).unwrap();
+            let forward_order_output = if GridBotContract::internal_order_is_empty(&forward_order) {
+                let virtual_order = GridBotContract::internal_get_first_forward_order(bot.clone(), pair.clone(), level.clone());
+                GridBotContract::internal_get_order_output(&virtual_order, false, &pair)
+            } else {
+                GridBotContract::internal_get_order_output(&forward_order, true, &pair)
+            };
+            let reverse_order = bot_orders.reverse_orders.get(level.clone() as u64).unwrap();
+            let reverse_order_output = if GridBotContract::internal_order_is_empty(&reverse_order) {
+                None
+            } else {
+                Some(GridBotContract::internal_get_order_output(&reverse_order, true, &pair))
+            };
+            level_orders.push(LevelOrdersOutput {
+                level,
+                forward_order: forward_order_output,
+                reverse_order: reverse_order_output,
+            });
+        }
+        return level_orders;
+    }
+
+    /// remaining amount of all can take orders on one side of pair, grouped by price, at most limit prices
+    /// Sell side start from the lowest price, Buy side start from the highest price
+    /// walk the orderbook in price order, the following virtual orders of a bot are merged in when its best virtual order is walked
+    pub fn query_pair_depth(&self, pair_id: String, side: OrderSide, price_from: Option<U128>, limit: usize) -> Vec<DepthOutput> {
+        require!(self.pair_map.contains_key(&pair_id), INVALID_PAIR_ID);
+        let pair = self.pair_map.get(&pair_id).unwrap();
+        let is_sell = side == OrderSide::Sell;
+        let token_sell = if is_sell { pair.base_token.clone() } else { pair.quote_token.clone() };
+        let mut depths: Vec<DepthOutput> = vec![];
+        let orderbook = match self.orderbook_map.get(&GridBotContract::internal_get_orderbook_key(&pair_id, &token_sell)) {
+            Some(orderbook) => orderbook,
+            None => return depths,
+        };
+        let price_from = price_from.map(|price| price.0).unwrap_or(if is_sell { 0 } else { u128::MAX });
+        let book_orders: Box<dyn Iterator<Item = (BookOrderKey, ())>> = if is_sell {
+            Box::new(orderbook.iter())
+        } else {
+            Box::new(orderbook.iter_rev())
+        };
+        let mut book_orders = book_orders.peekable();
+        // the following virtual orders of the walked bots, not in orderbook
+        let mut virtual_orders: BTreeMap<BookOrderKey, Order> = BTreeMap::new();
+        loop {
+            let virtual_key = if is_sell { virtual_orders.keys().next().cloned() } else { virtual_orders.keys().next_back().cloned() };
+            let from_book = match (book_orders.peek(), &virtual_key) {
+                (None, None) => break,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some((book_key, _)), Some(virtual_key)) => if is_sell { book_key.price <= virtual_key.price } else { book_key.price >= virtual_key.price },
+            };
+            let (book_order_key, order) = if from_book {
+                let (book_order_key, _) = book_orders.next().unwrap();
+                let bot = self.bot_map.get(&book_order_key.bot_id).unwrap();
+                if !self.internal_check_bot_can_take(&bot) {
+                    continue;
+                }
+                let bot_orders = self.order_map.get(&book_order_key.bot_id).unwrap();
+                let orders = if book_order_key.forward_or_reverse { bot_orders.forward_orders } else { bot_orders.reverse_orders };
+                let order = orders.get(book_order_key.level as u64).unwrap();
+                if !GridBotContract::internal_order_is_empty(&order) {
+                    (book_order_key, order)
+                } else {
+                    self.internal_add_next_virtual_order(&mut virtual_orders, &bot, &pair, &book_order_key);
+                    (book_order_key.clone(), GridBotContract::internal_get_first_forward_order(bot, pair.clone(), book_order_key.level))
+                }
+            } else {
+                let book_order_key = virtual_key.unwrap();
+                let order = virtual_orders.remove(&book_order_key).unwrap();
+                let bot = self.bot_map.get(&book_order_key.bot_id).unwrap();
+                self.internal_add_next_virtual_order(&mut virtual_orders, &bot, &pair, &book_order_key);
+                (book_order_key, order)
+            };
+            if GridBotContract::internal_order_is_filled(&order) || (is_sell && book_order_key.price < price_from) || (!is_sell && book_order_key.price > price_from) {
+                continue;
+            }
+            let (remaining_sell, remaining_buy) = GridBotContract::internal_get_order_remaining(&order);
+            let (base_amount, quote_amount) = if is_sell { (remaining_sell, remaining_buy) } else { (remaining_buy, remaining_sell) };
+            match depths.last_mut() {
+                Some(depth) if depth.price.0 == book_order_key.price => {
+                    depth.base_amount = U128::from(depth.base_amount.0 + base_amount.as_u128());
+                    depth.quote_amount = U128::from(depth.quote_amount.0 + quote_amount.as_u128());
+                    depth.order_count += 1;
+                },
+                _ => {
+                    if depths.len() >= limit {
+                        break;
+                    }
+                    depths.push(DepthOutput {
+                        price: U128::from(book_order_key.price),
+                        base_amount: U128::from(base_amount.as_u128()),
+                        quote_amount: U128::from(quote_amount.as_u128()),
+                        order_count: 1,
+                    });
+                },
+            }
+        }
+        return depths;
+    }
+
     pub fn estimate_calculate(&self, bot_id: String, forward_or_reverse: bool, level: usize, taker_order: &Order) -> (U256C, U256C, U256C, Order) {
         let (maker_order, _) = self.query_order(bot_id, forward_or_reverse, level);
         // matching check
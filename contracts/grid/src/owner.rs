@@ -1,5 +1,6 @@
 use near_sdk::{assert_one_yocto, require};
 use crate::*;
+use crate::legacy::{LEGACY_BOTS_PREFIX, LEGACY_PAIRS_PREFIX, OldGridBot, OldGridBotContract, OldPair};
 
 #[near_bindgen]
 impl GridBotContract {
@@ -20,15 +21,93 @@ impl GridBotContract {
     }
 
     /// Should only be called by this contract on migration.
-    /// This is NOOP implementation. KEEP IT if you haven't changed contract state.
-    /// If you have, you need to implement migration from old state
-    /// (keep the old struct with different name to deserialize it first).
-    /// After migration goes live, revert back to this implementation for next updates.
+    /// Migrate from the state before orderbook, the new collections start empty.
+    /// Bots and pairs are in LookupMap and can't be iterated, they stay under the old prefixes
+    /// and are moved by owner with migrate_pairs and migrate_bots, contract is paused until owner starts it.
+    /// After migration goes live, revert back to NOOP implementation for next updates.
     #[init(ignore_state)]
     #[private]
     pub fn migrate() -> Self {
-        let contract: GridBotContract = env::state_read().expect(CAN_NOT_READ_STATE);
-        contract
+        let old_contract: OldGridBotContract = env::state_read().expect(CAN_NOT_READ_STATE);
+        let status = if old_contract.status == GridStatus::Shutdown { GridStatus::Shutdown } else { GridStatus::Paused };
+        GridBotContract {
+            owner_id: old_contract.owner_id,
+            oracle: old_contract.oracle,
+            price_oracle: None,
+            oracle_valid_time: old_contract.oracle_valid_time,
+            status,
+            protocol_fee_rate: old_contract.protocol_fee_rate,
+            taker_fee_rate: old_contract.taker_fee_rate,
+            bot_map: LookupMap::new(StorageKey::Bots),
+            order_map: old_contract.order_map,
+            next_bot_id: old_contract.next_bot_id,
+            pair_map: LookupMap::new(StorageKey::Pairs),
+            pair_ids: Vector::new(StorageKey::PairIds),
+            protocol_fee_map: old_contract.protocol_fee_map,
+            global_balances_map: old_contract.global_balances_map,
+            deposit_limit_map: old_contract.deposit_limit_map,
+            user_balances_map: old_contract.user_balances_map,
+            user_locked_balances_map: old_contract.user_locked_balances_map,
+            market_user_map: old_contract.market_user_map,
+            wnear: old_contract.wnear,
+            operator_id: old_contract.operator_id,
+            refer_recommender_user_map: old_contract.refer_recommender_user_map,
+            refer_user_recommender_map: old_contract.refer_user_recommender_map,
+            refer_fee_map: old_contract.refer_fee_map,
+            refer_fee_rate: old_contract.refer_fee_rate,
+            base_create_storage_fee: old_contract.base_create_storage_fee,
+            storage_price_per_byte: old_contract.storage_price_per_byte,
+            per_grid_storage_fee: old_contract.per_grid_storage_fee,
+            expired_close_reward_rate: 0,
+            orderbook_map: LookupMap::new(StorageKey::OrderbookMainKey),
+            user_bots_map: LookupMap::new(StorageKey::UserBotsMainKey),
+            user_open_bots_map: LookupMap::new(StorageKey::UserOpenBotsMainKey),
+            pair_bots_map: LookupMap::new(StorageKey::PairBotsMainKey),
+            price_cache_map: LookupMap::new(StorageKey::PriceCacheMainKey),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccountMainKey),
+            pending_withdrawals_map: LookupMap::new(StorageKey::PendingWithdrawalMainKey),
+            // starts empty, reductions of assets from before are clamped and reported as token_discrepancy
+            token_accounting_map: LookupMap::new(StorageKey::TokenAccountingMainKey),
+            multi_token_map: LookupMap::new(StorageKey::MultiTokenMainKey),
+        }
+    }
+
+    /// move pairs from the state before orderbook, pair_ids keeps the order of pair_keys
+    #[payable]
+    pub fn migrate_pairs(&mut self, pair_keys: Vec<String>) {
+        self.assert_owner();
+        let mut legacy_pair_map: LookupMap<String, OldPair> = LookupMap::new(LEGACY_PAIRS_PREFIX);
+        for pair_key in pair_keys.iter() {
+            let old_pair = legacy_pair_map.remove(pair_key);
+            require!(old_pair.is_some(), INVALID_PAIR);
+            require!(!self.pair_map.contains_key(pair_key), PAIR_EXIST);
+            self.pair_map.insert(pair_key, &old_pair.unwrap().to_pair());
+            self.pair_ids.push(pair_key);
+        }
+    }
+
+    /// move bots from the state before orderbook, pairs must be moved first
+    /// bot_ids should be in created order, user_bots_map keeps the order of migration
+    /// not closed bots are indexed and put back into orderbook
+    #[payable]
+    pub fn migrate_bots(&mut self, bot_ids: Vec<String>) {
+        self.assert_owner();
+        let mut legacy_bot_map: LookupMap<String, OldGridBot> = LookupMap::new(LEGACY_BOTS_PREFIX);
+        for bot_id in bot_ids.iter() {
+            let old_bot = legacy_bot_map.remove(bot_id);
+            require!(old_bot.is_some(), BOT_NOT_EXIST);
+            require!(!self.bot_map.contains_key(bot_id), INVALID_BOT_ID);
+            let bot = old_bot.unwrap().to_grid_bot();
+            require!(self.pair_map.contains_key(&bot.pair_id), INVALID_PAIR);
+            self.bot_map.insert(bot_id, &bot);
+            self.internal_add_bot_index(&bot);
+            if bot.closed {
+                self.internal_remove_bot_index(&bot);
+            } else {
+                let pair = self.pair_map.get(&bot.pair_id).unwrap();
+                self.internal_restore_bot_to_orderbook(&bot, &pair);
+            }
+        }
     }
 }
 
@@ -0,0 +1,187 @@
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId, Balance};
+use near_sdk::json_types::U128;
+use crate::*;
+use crate::events::emit;
+
+/// NEP-145, storage of user's account record, token balances and the bots created with storage balance
+/// is charged from the deposit
+#[near_bindgen]
+impl StorageManagement for GridBotContract {
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min_balance = self.storage_balance_bounds().min.0;
+
+        if let Some(mut storage_account) = self.storage_accounts.get(&account_id) {
+            if registration_only {
+                if amount > 0 {
+                    self.internal_near_refund(&env::predecessor_account_id(), amount);
+                }
+            } else {
+                storage_account.deposit += amount;
+                self.storage_accounts.insert(&account_id, &storage_account);
+            }
+        } else {
+            require!(amount >= min_balance, LESS_STORAGE_FEE);
+            let deposit = if registration_only { min_balance } else { amount };
+            if amount > deposit {
+                self.internal_near_refund(&env::predecessor_account_id(), amount - deposit);
+            }
+            let initial_storage_usage = env::storage_usage();
+            self.storage_accounts.insert(&account_id, &StorageAccount { deposit, used_bytes: 0, tokens: vec![] });
+            self.internal_charge_storage(&account_id, initial_storage_usage);
+        }
+        emit::storage_deposit(&account_id, amount);
+        return self.storage_balance_of(account_id).unwrap();
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(self.storage_accounts.contains_key(&account_id), STORAGE_NOT_REGISTERED);
+        let available = self.internal_get_storage_available(&account_id);
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require!(amount <= available, LESS_STORAGE_BALANCE);
+        if amount > 0 {
+            let mut storage_account = self.storage_accounts.get(&account_id).unwrap();
+            storage_account.deposit -= amount;
+            self.storage_accounts.insert(&account_id, &storage_account);
+            self.internal_near_refund(&account_id, amount);
+            emit::storage_withdraw(&account_id, amount);
+        }
+        return self.storage_balance_of(account_id).unwrap();
+    }
+
+    /// tokens registered with storage balance are unregistered, their balances must be 0,
+    /// then the whole deposit is refunded
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        require!(!force.unwrap_or(false), INVALID_PARAM);
+        let account_id = env::predecessor_account_id();
+        if !self.storage_accounts.contains_key(&account_id) {
+            return false;
+        }
+        let open_bots_count = self.user_open_bots_map.get(&account_id).map(|bots| bots.len()).unwrap_or(0);
+        require!(open_bots_count == 0, CAN_NOT_UNREGISTER);
+        let storage_account = self.storage_accounts.get(&account_id).unwrap();
+        for token in storage_account.tokens.iter() {
+            require!(self.internal_get_user_balance(&account_id, token) == U256C::from(0), CAN_NOT_UNREGISTER);
+            require!(self.internal_get_user_locked_balance(&account_id, token) == U256C::from(0), CAN_NOT_UNREGISTER);
+            self.internal_unregister_token_for_user(&account_id, token);
+        }
+        self.storage_accounts.remove(&account_id);
+        if storage_account.deposit > 0 {
+            self.internal_near_refund(&account_id, storage_account.deposit);
+        }
+        emit::storage_unregister(&account_id, storage_account.deposit);
+        return true;
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128::from(self.base_create_storage_fee),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        let storage_account = self.storage_accounts.get(&account_id)?;
+        return Some(StorageBalance {
+            total: U128::from(storage_account.deposit),
+            available: U128::from(self.internal_get_storage_available(&account_id)),
+        });
+    }
+}
+
+impl GridBotContract {
+    pub fn internal_get_storage_available(&self, account_id: &AccountId) -> Balance {
+        return self.storage_accounts.get(account_id)
+            .map(|storage_account| storage_account.deposit.saturating_sub(self.storage_price_per_byte * Balance::from(storage_account.used_bytes)))
+            .unwrap_or(0);
+    }
+
+    /// charge storage used since initial_storage_usage to account's deposit
+    pub fn internal_charge_storage(&mut self, account_id: &AccountId, initial_storage_usage: u64) {
+        let mut storage_account = self.storage_accounts.get(account_id).unwrap();
+        if env::storage_usage() > initial_storage_usage {
+            storage_account.used_bytes += env::storage_usage() - initial_storage_usage;
+        }
+        require!(storage_account.deposit >= self.storage_price_per_byte * Balance::from(storage_account.used_bytes), LESS_STORAGE_BALANCE);
+        self.storage_accounts.insert(account_id, &storage_account);
+    }
+
+    /// register token for user paid by storage deposit, false if user has no storage account
+    pub fn internal_register_token_with_storage(&mut self, user: &AccountId, token: &AccountId) -> bool {
+        if !self.storage_accounts.contains_key(user) {
+            return false;
+        }
+        let initial_storage_usage = env::storage_usage();
+        self.internal_register_token_for_user(user, token);
+        let mut storage_account = self.storage_accounts.get(user).unwrap();
+        storage_account.tokens.push(token.clone());
+        self.storage_accounts.insert(user, &storage_account);
+        self.internal_charge_storage(user, initial_storage_usage);
+        return true;
+    }
+
+    /// draw the shortfall of paid to required from user's available storage balance,
+    /// None if available storage balance is not enough, then nothing is drawn
+    pub fn internal_draw_storage_balance(&mut self, user: &AccountId, paid: Balance, required: Balance) -> Option<Balance> {
+        if paid >= required {
+            return Some(0);
+        }
+        let shortfall = required - paid;
+        if self.internal_get_storage_available(user) < shortfall {
+            return None;
+        }
+        let mut storage_account = self.storage_accounts.get(user).unwrap();
+        storage_account.deposit -= shortfall;
+        self.storage_accounts.insert(user, &storage_account);
+        return Some(shortfall);
+    }
+
+    /// return the NEAR drawn by internal_draw_storage_balance, to user's wallet if storage account is unregistered
+    pub fn internal_return_storage_draw(&mut self, user: &AccountId, drawn: Balance) {
+        if drawn == 0 {
+            return;
+        }
+        match self.storage_accounts.get(user) {
+            Some(mut storage_account) => {
+                storage_account.deposit += drawn;
+                self.storage_accounts.insert(user, &storage_account);
+            }
+            None => self.internal_near_refund(user, drawn),
+        }
+    }
+
+    /// bot created with storage balance, the NEAR paid for it goes to user's storage deposit,
+    /// and the bytes of bot and its orders are charged to the storage account
+    pub fn internal_charge_bot_storage(&mut self, user: &AccountId, grid_bot: &mut GridBot, paid: Balance, initial_storage_usage: u64) {
+        if !self.storage_accounts.contains_key(user) {
+            // unregistered while creating
+            self.internal_refund_deposit(paid, initial_storage_usage, user);
+            return;
+        }
+        grid_bot.storage_used_bytes = env::storage_usage() - initial_storage_usage;
+        // same size, storage usage is not changed
+        self.bot_map.insert(&grid_bot.bot_id, grid_bot);
+        let mut storage_account = self.storage_accounts.get(user).unwrap();
+        storage_account.deposit += paid;
+        storage_account.used_bytes += grid_bot.storage_used_bytes;
+        require!(storage_account.deposit >= self.storage_price_per_byte * Balance::from(storage_account.used_bytes), LESS_STORAGE_BALANCE);
+        self.storage_accounts.insert(user, &storage_account);
+    }
+
+    /// release the bytes of closed bot from user's storage account, fee is taken from the deposit
+    pub fn internal_release_bot_storage(&mut self, user: &AccountId, bot: &GridBot, fee: Balance) {
+        let mut storage_account = self.storage_accounts.get(user).unwrap();
+        storage_account.used_bytes -= bot.storage_used_bytes;
+        storage_account.deposit -= fee;
+        self.storage_accounts.insert(user, &storage_account);
+    }
+}
@@ -1,14 +1,17 @@
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_contract_standards::storage_management::StorageBalance;
-use near_sdk::{AccountId, Balance, env, is_promise_success, log, Promise, PromiseOrValue, require, PromiseError};
+use near_sdk::{AccountId, Balance, env, Gas, is_promise_success, log, Promise, PromiseOrValue, PromiseResult, require, PromiseError};
+use near_sdk::serde_json::json;
 use crate::*;
 use near_sdk::json_types::U128;
 use near_sdk::{ext_contract, near_bindgen};
 use crate::events::emit;
+use crate::multi_token::ext_multi_token;
 
 #[ext_contract(ext_fungible_token)]
 pub trait FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_transfer_call(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>, msg: String) -> U128;
     fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance;
 }
 
@@ -28,6 +31,9 @@ impl FungibleTokenReceiver for GridBotContract {
             } else {
                 return PromiseOrValue::Value(U128::from(0));
             }
+        } else if let Ok(create_bot_request) = serde_json::from_str::<CreateBotRequest>(&msg) {
+            let unused = self.internal_parse_create_bot_request(&sender_id, &token_in, amount, create_bot_request);
+            return PromiseOrValue::Value(unused);
         } else {
             let left = self.internal_parse_take_request(&sender_id, &token_in, amount, msg);
             return PromiseOrValue::Value(left);
@@ -54,7 +60,20 @@ impl GridBotContract {
         )
     }
 
-    pub fn internal_ft_transfer(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
+    /// ft_transfer, or mt_transfer for NEP-245 token
+    pub fn internal_token_transfer(&self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
+        if let Some(multi_token) = self.multi_token_map.get(token_id) {
+            return ext_multi_token::ext(multi_token.contract_id)
+                .with_attached_deposit(ONE_YOCTO)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .mt_transfer(
+                    account_id.clone(),
+                    multi_token.token_id,
+                    amount.into(),
+                    None,
+                    None,
+                );
+        }
         ext_fungible_token::ext(token_id.clone())
             .with_attached_deposit(ONE_YOCTO)
             .with_static_gas(GAS_FOR_FT_TRANSFER)
@@ -62,7 +81,31 @@ impl GridBotContract {
                 account_id.clone(),
                 amount.into(),
                 None,
-            ).then(
+            )
+    }
+
+    /// ft_balance_of(current_account), or mt_balance_of for NEP-245 token
+    pub fn internal_token_balance_of(&self, token_id: &AccountId) -> Promise {
+        if let Some(multi_token) = self.multi_token_map.get(token_id) {
+            return Promise::new(multi_token.contract_id)
+                .function_call(
+                    "mt_balance_of".to_string(),
+                    json!({"account_id": env::current_account_id(), "token_id": multi_token.token_id}).to_string().into_bytes(),
+                    0,
+                    Gas(0),
+                );
+        }
+        Promise::new(token_id.clone())
+            .function_call(
+                "ft_balance_of".to_string(),
+                json!({"account_id": env::current_account_id()}).to_string().into_bytes(),
+                0,
+                Gas(0),
+            )
+    }
+
+    pub fn internal_ft_transfer(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
+        self.internal_token_transfer(account_id, token_id, amount).then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
                 .after_ft_transfer(
@@ -73,15 +116,51 @@ impl GridBotContract {
         )
     }
 
+    /// transfer user's asset to receiver, failed or unused amount is returned to user
+    pub fn internal_ft_transfer_to(&mut self, account_id: &AccountId, receiver_id: &AccountId, token_id: &AccountId, amount: Balance, msg: Option<String>) -> Promise {
+        let is_call = msg.is_some();
+        let transfer = if let Some(msg) = msg {
+            if let Some(multi_token) = self.multi_token_map.get(token_id) {
+                ext_multi_token::ext(multi_token.contract_id)
+                    .with_attached_deposit(ONE_YOCTO)
+                    .with_static_gas(GAS_FOR_FT_TRANSFER_CALL)
+                    .mt_transfer_call(
+                        receiver_id.clone(),
+                        multi_token.token_id,
+                        amount.into(),
+                        None,
+                        None,
+                        msg,
+                    )
+            } else {
+                ext_fungible_token::ext(token_id.clone())
+                    .with_attached_deposit(ONE_YOCTO)
+                    .with_static_gas(GAS_FOR_FT_TRANSFER_CALL)
+                    .ft_transfer_call(
+                        receiver_id.clone(),
+                        amount.into(),
+                        None,
+                        msg,
+                    )
+            }
+        } else {
+            self.internal_token_transfer(receiver_id, token_id, amount)
+        };
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                .after_ft_transfer_to(
+                    account_id.clone(),
+                    receiver_id.clone(),
+                    token_id.clone(),
+                    amount.into(),
+                    is_call,
+                )
+        )
+    }
+
     pub fn internal_ft_transfer_protocol_fee(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
-        ext_fungible_token::ext(token_id.clone())
-            .with_attached_deposit(ONE_YOCTO)
-            .with_static_gas(GAS_FOR_FT_TRANSFER)
-            .ft_transfer(
-                account_id.clone(),
-                amount.into(),
-                None,
-            ).then(
+        self.internal_token_transfer(account_id, token_id, amount).then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
                 .after_ft_transfer_protocol_fee(
@@ -93,14 +172,7 @@ impl GridBotContract {
     }
 
     pub fn internal_ft_transfer_refer_fee(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
-        ext_fungible_token::ext(token_id.clone())
-            .with_attached_deposit(ONE_YOCTO)
-            .with_static_gas(GAS_FOR_FT_TRANSFER)
-            .ft_transfer(
-                account_id.clone(),
-                amount.into(),
-                None,
-            ).then(
+        self.internal_token_transfer(account_id, token_id, amount).then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
                 .after_ft_transfer_refer_fee(
@@ -126,14 +198,7 @@ impl GridBotContract {
     }
 
     pub fn internal_ft_transfer_unowned_asset(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) -> Promise {
-        ext_fungible_token::ext(token_id.clone())
-            .with_attached_deposit(ONE_YOCTO)
-            .with_static_gas(GAS_FOR_FT_TRANSFER)
-            .ft_transfer(
-                account_id.clone(),
-                amount.into(),
-                None,
-            ).then(
+        self.internal_token_transfer(account_id, token_id, amount).then(
             Self::ext(env::current_account_id())
                 .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
                 .after_ft_transfer_unowned_asset(
@@ -151,6 +216,8 @@ trait ExtSelf {
                              -> bool;
     fn after_ft_transfer(&mut self, account_id: AccountId, token_id: AccountId, amount: U128)
                          -> bool;
+    fn after_ft_transfer_to(&mut self, account_id: AccountId, receiver_id: AccountId, token_id: AccountId, amount: U128, is_call: bool)
+                         -> U128;
     fn after_ft_transfer_protocol_fee(&mut self, account_id: AccountId, token_id: AccountId, amount: U128)
                          -> bool;
     fn after_ft_transfer_refer_fee(&mut self, account_id: AccountId, token_id: AccountId, amount: U128)
@@ -158,6 +225,7 @@ trait ExtSelf {
     fn after_ft_transfer_unowned_asset(&mut self, account_id: AccountId, token_id: AccountId, amount: U128)
                          -> bool;
     fn after_ft_balance_of_for_withdraw_unowned_asset(&mut self, token_id: AccountId, to_user: AccountId, #[callback_result] last_result: Result<U128, PromiseError>);
+    fn after_ft_balance_of_for_reconcile(&mut self, token_id: AccountId, #[callback_result] last_result: Result<U128, PromiseError>);
 }
 
 #[near_bindgen]
@@ -190,7 +258,7 @@ impl ExtSelf for GridBotContract {
         if !promise_success.clone() {
             emit::withdraw_failed(&account_id, amount.clone().0, &token_id);
             // self.internal_increase_withdraw_failed_asset(&account_id, &token_id, &(U256C::from(amount.clone().0)));
-            self.internal_increase_asset(&account_id, &token_id, &(U256C::from(amount.clone().0)));
+            self.internal_add_pending_withdrawal(&account_id, &token_id, amount.clone().0, FT_TRANSFER_FAILED);
         } else {
             emit::withdraw_succeeded(&account_id, amount.clone().0, &token_id);
             // reduce from global asset
@@ -199,6 +267,42 @@ impl ExtSelf for GridBotContract {
         promise_success
     }
 
+    #[private]
+    fn after_ft_transfer_to(
+        &mut self,
+        account_id: AccountId,
+        receiver_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+        is_call: bool,
+    ) -> U128 {
+        // ft_transfer_call returns the amount used by receiver
+        let used = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                if is_call {
+                    // mt_transfer_call returns the used amounts of token_ids
+                    serde_json::from_slice::<U128>(&value)
+                        .or_else(|_| serde_json::from_slice::<Vec<U128>>(&value).map(|used| used.get(0).cloned().unwrap_or(amount)))
+                        .map(|used| used.0.min(amount.0)).unwrap_or(amount.0)
+                } else {
+                    amount.0
+                }
+            },
+            _ => 0,
+        };
+        if used < amount.0 {
+            // credit the original user, not the receiver
+            emit::withdraw_failed(&account_id, amount.0 - used, &token_id);
+            self.internal_increase_asset(&account_id, &token_id, &(U256C::from(amount.0 - used)));
+        }
+        if used > 0 {
+            emit::withdraw_to_succeeded(&account_id, &receiver_id, used, &token_id);
+            // reduce from global asset
+            self.internal_reduce_global_asset(&token_id, &(U256C::from(used)));
+        }
+        U128::from(used)
+    }
+
     #[private]
     fn after_ft_transfer_protocol_fee(
         &mut self,
@@ -258,10 +362,19 @@ impl ExtSelf for GridBotContract {
         if let Ok(balance) = last_result {
             let recorded_balance = self.internal_get_global_balance(&token_id);
             require!(balance.0 >= recorded_balance.as_u128(), INVALID_BALANCE);
-            let can_withdraw_amount = balance.0 - recorded_balance.as_u128();
-            self.internal_withdraw_unowned_asset(&to_user, &token_id, U256C::from(can_withdraw_amount));
+            let can_withdraw_amount = self.internal_get_unowned_amount(&token_id, U256C::from(balance.0));
+            self.internal_withdraw_unowned_asset(&to_user, &token_id, can_withdraw_amount);
         } else {
             log!(WITHDRAW_UNOWNED_ASSET_ERROR);
         }
     }
+
+    #[private]
+    fn after_ft_balance_of_for_reconcile(&mut self, token_id: AccountId, #[callback_result] last_result: Result<U128, PromiseError>) {
+        if let Ok(balance) = last_result {
+            self.internal_reconcile(&token_id, balance.0);
+        } else {
+            log!(RECONCILE_ERROR);
+        }
+    }
 }
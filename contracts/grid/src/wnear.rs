@@ -12,7 +12,7 @@ pub trait WNEARNear {
 }
 
 impl GridBotContract {
-    // note: withdraw_near just can be used by user withdraw, because, if error, will add user's balance
+    // note: withdraw_near just can be used by user withdraw, because, if error, will add user's pending withdrawal
     pub fn withdraw_near(&mut self, user: &AccountId, amount: u128) {
         ext_wnear::ext(self.wnear.clone())
             .with_attached_deposit(ONE_YOCTO)
@@ -26,8 +26,22 @@ impl GridBotContract {
             );
     }
 
+    pub fn deposit_near_to_get_wnear(&mut self, user: &AccountId, amount: u128) {
+        ext_wnear::ext(self.wnear.clone())
+            .with_attached_deposit(amount)
+            .near_deposit()
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_deposit_near(
+                        user,
+                        amount,
+                    )
+            );
+    }
+
     pub fn deposit_near_to_get_wnear_for_create_bot(&mut self, pair: &Pair, user: &AccountId, slippage: u16, entry_price: &U256C,
-                                     grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128) {
+                                     grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128, storage_drawn: u128) {
         ext_wnear::ext(self.wnear.clone())
             .with_attached_deposit(amount)
             // .with_static_gas(GAS_FOR_CREATE_BOT_AFTER_NEAR)
@@ -43,7 +57,8 @@ impl GridBotContract {
                     grid_bot,
                     amount,
                     recommender,
-                    storage_fee
+                    storage_fee,
+                    storage_drawn
                 )
         );
     }
@@ -53,33 +68,34 @@ impl GridBotContract {
 #[ext_contract(ext_self)]
 trait ExtSelf {
     fn after_wrap_near_for_create_bot(&mut self, pair: &Pair, user: &AccountId, slippage: u16, entry_price: &U256C,
-                       grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128) -> bool;
+                       grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128, storage_drawn: u128) -> bool;
     fn after_withdraw_near(&mut self, user: &AccountId, amount: u128) -> bool;
+    fn after_deposit_near(&mut self, user: &AccountId, amount: u128) -> bool;
 }
 
 #[near_bindgen]
 impl ExtSelf for GridBotContract {
     #[private]
     // just used for create bot
-    fn after_wrap_near_for_create_bot(&mut self, pair: &Pair, user: &AccountId, slippage: u16, entry_price: &U256C, grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128) -> bool {
+    fn after_wrap_near_for_create_bot(&mut self, pair: &Pair, user: &AccountId, slippage: u16, entry_price: &U256C, grid_bot: &mut GridBot, amount: u128, recommender: Option<AccountId>, storage_fee: u128, storage_drawn: u128) -> bool {
         let promise_success = is_promise_success();
         if !promise_success.clone() {
             // refund token and near
-            self.internal_create_bot_refund_with_near(user, pair, amount + storage_fee, WRAP_TO_WNEAR_ERROR);
+            self.internal_create_bot_refund_with_storage(user, pair, amount + storage_fee, storage_drawn, WRAP_TO_WNEAR_ERROR);
             emit::wrap_near_error(user, 0, amount, true);
         } else {
             // deposit
             if !self.internal_deposit(&user.clone(), &self.wnear.clone(), U128::from(amount)) {
                 // maybe just need hande one token, but it's ok, no problem
                 self.internal_increase_asset(user, &self.wnear.clone(), &U256C::from(amount.clone()));
-                self.internal_create_bot_refund_with_near(user, pair, storage_fee, WRAP_TO_WNEAR_ERROR);
+                self.internal_create_bot_refund_with_storage(user, pair, storage_fee, storage_drawn, WRAP_TO_WNEAR_ERROR);
                 emit::wrap_near_error(user, 0, amount, true);
             } else {
                 // request price
                 if pair.require_oracle {
-                    self.get_price_for_create_bot(pair, user, slippage, entry_price, grid_bot, recommender, storage_fee);
+                    self.get_price_for_create_bot(pair, user, slippage, entry_price, grid_bot, recommender, storage_fee, storage_drawn);
                 } else {
-                    self.internal_create_bot(None, None, user, slippage, entry_price, pair, recommender, storage_fee, grid_bot);
+                    self.internal_create_bot(None, None, user, slippage, entry_price, pair, recommender, storage_fee, storage_drawn, grid_bot);
                 }
             }
         }
@@ -91,10 +107,24 @@ impl ExtSelf for GridBotContract {
         let promise_success = is_promise_success();
         if !promise_success.clone() {
             emit::wrap_near_error(user, 0, amount, false);
-            self.internal_increase_asset(user, &self.wnear.clone(), &(U256C::from(amount)));
+            self.internal_add_pending_withdrawal(user, &self.wnear.clone(), amount, NEAR_WITHDRAW_FAILED);
         } else {
             self.internal_ft_transfer_near(user, amount, true);
         }
         promise_success
     }
+    #[private]
+    fn after_deposit_near(&mut self, user: &AccountId, amount: u128) -> bool {
+        let promise_success = is_promise_success();
+        if !promise_success.clone() {
+            // refund near
+            self.internal_ft_transfer_near_without_result(user, amount);
+            emit::wrap_near_error(user, 0, amount, true);
+        } else if !self.internal_deposit(&user.clone(), &self.wnear.clone(), U128::from(amount)) {
+            // wnear is received but not credited, pay back as near by retry_withdrawals
+            self.internal_increase_global_asset(&self.wnear.clone(), &U256C::from(amount));
+            self.internal_add_pending_withdrawal(user, &self.wnear.clone(), amount, DEPOSIT_NEAR_REJECTED);
+        }
+        promise_success
+    }
 }
@@ -0,0 +1,97 @@
+use std::str::FromStr;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{GridType, OrderSide, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+#[tokio::test]
+async fn query_bots_by_user_and_pair() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    let pairs = gridbot_contract.query_pairs(0, 10).await?;
+    require!(pairs.len() == 1);
+    require!(pairs[0].pair_id == pair_id);
+    require!(pairs[0].base_min_deposit.0 == 1000);
+
+    // deposit
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    // create two bots
+    for _ in 0..2 {
+        check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                                  U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                                  U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                                  U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                                  U256C::from(7000000000000000000 as u128)).await);
+    }
+    let maker_account_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let user_bots = gridbot_contract.query_user_bots(&maker_account_id, 0, 10, false).await?;
+    require!(user_bots.len() == 2);
+    let pair_bots = gridbot_contract.query_pair_bots(pair_id.clone(), None, 10).await?;
+    require!(pair_bots.len() == 2);
+    require!(pair_bots[0].bot_id == "GRID:1".to_string());
+    let pair_bots = gridbot_contract.query_pair_bots(pair_id.clone(), Some("GRID:1".to_string()), 10).await?;
+    require!(pair_bots.len() == 1);
+    require!(pair_bots[0].bot_id == "GRID:2".to_string());
+
+    // 15 buy levels and 10 sell levels
+    let level_orders = gridbot_contract.query_bot_orders("GRID:2".to_string(), 10, 10).await?;
+    require!(level_orders.len() == 10);
+    require!(level_orders[0].level == 10);
+    require!(!level_orders[0].forward_order.placed);
+    require!(level_orders[0].reverse_order.is_none());
+    // level 10 buy: 100000000, 2000000000 + 10000000 * 10=2100000000
+    require!(level_orders[0].forward_order.price.0 == 21000000000000000000);
+    let level_orders = gridbot_contract.query_bot_orders("GRID:2".to_string(), 20, 10).await?;
+    require!(level_orders.len() == 5);
+
+    // two same bots, the best buy price is level 14: 100000000, 2140000000
+    let depth = gridbot_contract.query_pair_depth(pair_id.clone(), OrderSide::Buy, None, 3).await?;
+    require!(depth.len() == 3);
+    require!(depth[0].price.0 == 21400000000000000000);
+    require!(depth[0].base_amount.0 == 200000000);
+    require!(depth[0].quote_amount.0 == 4280000000);
+    require!(depth[0].order_count == 2);
+    require!(depth[1].price.0 == 21300000000000000000);
+    // the best sell price is level 15: 100000000, 3000000000 - 10000000 * 9=2910000000
+    let depth = gridbot_contract.query_pair_depth(pair_id.clone(), OrderSide::Sell, Some(U128::from(29100000000000000000)), 1).await?;
+    require!(depth[0].price.0 == 29100000000000000000);
+
+    // closed bot only left in user's history
+    check_success(gridbot_contract.close_bot(&maker_account, "GRID:1".to_string()).await);
+    let user_bots = gridbot_contract.query_user_bots(&maker_account_id, 0, 10, false).await?;
+    require!(user_bots.len() == 1);
+    require!(user_bots[0].bot_id == "GRID:2".to_string());
+    require!(gridbot_contract.query_user_bots_count(&maker_account_id, false).await? == 1);
+    let user_bots = gridbot_contract.query_user_bots(&maker_account_id, 0, 10, true).await?;
+    require!(user_bots.len() == 2);
+    require!(user_bots[0].closed);
+    require!(gridbot_contract.query_user_bots_count(&maker_account_id, true).await? == 2);
+    // open bots are paged over the open index, so no short page
+    let user_bots = gridbot_contract.query_user_bots(&maker_account_id, 0, 1, false).await?;
+    require!(user_bots.len() == 1);
+    require!(!user_bots[0].closed);
+    let user_bots = gridbot_contract.query_user_bots(&maker_account_id, 1, 1, false).await?;
+    require!(user_bots.len() == 0);
+    let pair_bots = gridbot_contract.query_pair_bots(pair_id.clone(), None, 10).await?;
+    require!(pair_bots.len() == 1);
+    let pairs = gridbot_contract.query_pairs(0, 10).await?;
+    require!(pairs[0].bot_count == 1);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{GridType, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+pub fn get_event_amount(logs: &Vec<&str>, event: &str, field: &str) -> u128 {
+    let event_log = logs.iter().find(|log| log.contains(&format!("\"event\":\"{}\"", event))).unwrap();
+    let event_json: serde_json::Value = serde_json::from_str(event_log.trim_start_matches("EVENT_JSON:")).unwrap();
+    return event_json["data"][0][field].as_str().unwrap().parse::<u128>().unwrap();
+}
+
+#[tokio::test]
+async fn close_expired_bot() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    // reward rate is at most 50% of storage refund
+    check_failure(gridbot_contract.set_expired_close_reward_rate(&owner, 500001).await, "INVALID_EXPIRED_CLOSE_REWARD_RATE");
+    let expired_close_reward_rate = 500000 as u128;
+    check_success(gridbot_contract.set_expired_close_reward_rate(&owner, expired_close_reward_rate).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    // valid for 60s
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    let valid_until_time = get_time_stamp() * 1000 + 60000;
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(valid_until_time),
+                                              U256C::from(0)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // not expired yet
+    check_failure(gridbot_contract.close_expired_bot(&taker_account, next_bot_id.clone()).await, "BOT_NOT_EXPIRED");
+
+    let now = get_time_stamp() * 1000;
+    if now <= valid_until_time {
+        tokio::time::sleep(Duration::from_millis(valid_until_time - now + 5000)).await;
+    }
+
+    let bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    let maker_eth_before = eth_token_contract.ft_balance_of(&maker_account).await?.0;
+    let maker_usdc_before = usdc_token_contract.ft_balance_of(&maker_account).await?.0;
+    let result = gridbot_contract.close_expired_bot(&taker_account, next_bot_id.clone()).await?;
+    require!(result.is_success());
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().closed);
+
+    // assets go back to bot's owner
+    require!(eth_token_contract.ft_balance_of(&maker_account).await?.0 == maker_eth_before + bot.total_base_amount.as_u128());
+    require!(usdc_token_contract.ft_balance_of(&maker_account).await?.0 == maker_usdc_before + bot.total_quote_amount.as_u128());
+
+    // caller is paid storage_refund * rate / PROTOCOL_FEE_DENOMINATOR, owner gets the rest
+    let logs = result.logs();
+    let keeper_log = format!("\"keeper\":\"{}\"", taker_account.id());
+    require!(logs.iter().any(|log| log.contains("keeper_reward") && log.contains(&keeper_log)));
+    let storage_reward = get_event_amount(&logs, "keeper_reward", "reward");
+    let refund = get_event_amount(&logs, "close_bot", "refund");
+    log!("storage_reward:{}, refund:{}", storage_reward, refund);
+    require!(storage_reward > 0);
+    require!(storage_reward == (refund + storage_reward) * expired_close_reward_rate / 1000000);
+
+    Ok(())
+}
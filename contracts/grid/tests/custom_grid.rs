@@ -0,0 +1,55 @@
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{RequestGridLevel, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+pub fn grid_level(base_amount: u128, quote_amount: u128) -> RequestGridLevel {
+    return RequestGridLevel { base_amount: U128::from(base_amount), quote_amount: U128::from(quote_amount) };
+}
+
+#[tokio::test]
+async fn custom_grid() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    // liquidity concentrated around 20~21
+    let custom_levels = vec![grid_level(100000000, 1900000000), grid_level(300000000, 6000000000), grid_level(300000000, 6300000000), grid_level(100000000, 2200000000)];
+    check_success(gridbot_contract.create_custom_bot(&maker_account, pair_id.clone(), true, 2, 2, custom_levels, U256C::from(get_time_stamp() * 1000 + 3600000000)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.total_quote_amount == U256C::from(7900000000 as u128));
+    require!(grid_bot.total_base_amount == U256C::from(400000000));
+
+    let level_orders = gridbot_contract.query_bot_orders(next_bot_id.clone(), 0, 4).await?;
+    require!(level_orders.len() == 4);
+    // buy level sell quote
+    require!(level_orders[1].forward_order.order.amount_sell.0 == 6000000000);
+    require!(level_orders[1].forward_order.order.amount_buy.0 == 300000000);
+    // sell level sell base
+    require!(level_orders[2].forward_order.order.amount_sell.0 == 300000000);
+    require!(level_orders[2].forward_order.order.amount_buy.0 == 6300000000);
+
+    // price must rise with level, no bot created
+    let custom_levels = vec![grid_level(100000000, 2100000000), grid_level(100000000, 2000000000)];
+    check_success(gridbot_contract.create_custom_bot(&maker_account, pair_id.clone(), true, 1, 1, custom_levels, U256C::from(get_time_stamp() * 1000 + 3600000000)).await);
+    require!(gridbot_contract.query_bot(format!("GRID:{}", "2".to_string())).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::str::FromStr;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::U256C;
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+#[tokio::test]
+async fn multi_token_deposit() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, _, usdc_token_contract) = create_contract().await?;
+    // maker acts as the NEP-245 contract
+    let mt_contract_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let taker_id = AccountId::from_str(taker_account.id()).expect("Invalid AccountId");
+
+    let result = gridbot_contract.register_multi_token(&owner, &mt_contract_id, "lp-1".to_string()).await?;
+    let mt_token = result.json::<AccountId>().unwrap();
+    log!("multi token key:{}", mt_token);
+    require!(gridbot_contract.query_multi_token(&mt_token).await?.unwrap().token_id == "lp-1");
+    let result = gridbot_contract.register_multi_token(&owner, &mt_contract_id, "lp-1".to_string()).await?;
+    require!(result.is_failure());
+
+    check_success(gridbot_contract.register_pair(&owner, &mt_token, &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+
+    check_success(gridbot_contract.storage_deposit(&taker_account).await);
+    check_success(gridbot_contract.mt_on_transfer(&maker_account, &taker_id, vec!["lp-1".to_string()], vec![U128::from(100000)], "".to_string()).await);
+    require!(gridbot_contract.query_user_balance(&taker_id, mt_token.clone()).await?.unwrap().0 == 100000);
+
+    // token not registered, all amounts are refunded
+    let result = gridbot_contract.mt_on_transfer(&maker_account, &taker_id, vec!["lp-1".to_string(), "lp-2".to_string()], vec![U128::from(100000), U128::from(200000)], "".to_string()).await?;
+    let unused = result.json::<Vec<U128>>().unwrap();
+    require!(unused.len() == 2);
+    require!(unused[0].0 == 100000 && unused[1].0 == 200000);
+    require!(gridbot_contract.query_user_balance(&taker_id, mt_token.clone()).await?.unwrap().0 == 100000);
+
+    Ok(())
+}
@@ -335,3 +335,64 @@ async fn require_oracle() -> Result<(), workspaces::error::Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn create_bot_with_mock_oracle_failure() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, _, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(30000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(300000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    // create bot doesn't panic on oracle failure, create_bot_error is emitted and user's balances are withdrawn
+    // oracle returns nothing
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::ReturnNone).await);
+    let result = gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await?;
+    require!(result.logs().iter().any(|log| log.contains("create_bot_error") && log.contains("INVALID_PAIR_PRICE_LENGTH")));
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 0);
+    require!(gridbot_contract.query_user_balance(&maker_id, usdc_token_contract.get_account_id()).await?.unwrap().0 == 0);
+
+    // oracle price is stale, pair price = 2000 * 10^-2 / (100000000 * 10^-8) = 20
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::Normal).await);
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, Some(get_time_stamp() as i64 - 3600)).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+    let result = gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await?;
+    require!(result.logs().iter().any(|log| log.contains("create_bot_error") && log.contains("INVALID_PRICE")));
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 0);
+    require!(gridbot_contract.query_user_balance(&maker_id, usdc_token_contract.get_account_id()).await?.unwrap().0 == 0);
+
+    // fresh price, entry price 20
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    let result = gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await?;
+    require!(result.is_success());
+    require!(!result.logs().iter().any(|log| log.contains("create_bot_error")));
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    Ok(())
+}
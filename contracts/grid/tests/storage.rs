@@ -0,0 +1,102 @@
+use std::str::FromStr;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{GridType, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+#[tokio::test]
+async fn storage_management() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, _, gridbot_contract, eth_token_contract, _) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    require!(gridbot_contract.storage_balance_of(&maker_id).await?.is_none());
+
+    // contract registers itself on token by owner
+    check_success(gridbot_contract.storage_deposit_for_token(&owner, &eth_token_contract.get_account_id(), 100000000000000000000000).await);
+    let result = gridbot_contract.storage_deposit_for_token(&maker_account, &eth_token_contract.get_account_id(), 100000000000000000000000).await?;
+    require!(result.is_failure());
+
+    check_success(gridbot_contract.storage_deposit(&maker_account).await);
+    let storage_balance = gridbot_contract.storage_balance_of(&maker_id).await?.unwrap();
+    log!("storage_balance total:{}, available:{}", storage_balance.total.0, storage_balance.available.0);
+    require!(storage_balance.available.0 < storage_balance.total.0);
+
+    // token registered on first deposit, charged from storage balance
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 10000000000000000000000);
+    let after_register = gridbot_contract.storage_balance_of(&maker_id).await?.unwrap();
+    require!(after_register.available.0 < storage_balance.available.0);
+
+    // can not withdraw more than available
+    let result = gridbot_contract.storage_withdraw(&maker_account, Some(U128::from(after_register.total.0))).await?;
+    require!(result.is_failure());
+    check_success(gridbot_contract.storage_withdraw(&maker_account, Some(U128::from(1000))).await);
+    require!(gridbot_contract.storage_balance_of(&maker_id).await?.unwrap().available.0 == after_register.available.0 - 1000);
+
+    // token registered with storage balance still has balance
+    check_failure(gridbot_contract.storage_unregister(&maker_account).await, "CAN_NOT_UNREGISTER");
+    check_success(gridbot_contract.withdraw(&maker_account, eth_token_contract.get_account_id()).await);
+    // token is unregistered, whole deposit is refunded
+    check_success(gridbot_contract.storage_unregister(&maker_account).await);
+    require!(gridbot_contract.storage_balance_of(&maker_id).await?.is_none());
+    require!(!gridbot_contract.query_user_token_registered(&maker_id, eth_token_contract.get_account_id()).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_bot_by_transfer() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+    check_success(eth_token_contract.ft_mint(&taker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    // no storage balance, whole amount returned
+    let taker_eth_before = eth_token_contract.ft_balance_of(&taker_account).await?.0;
+    check_success(gridbot_contract.create_bot_by_transfer(&eth_token_contract, &taker_account, 10000000000000000000000, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                                          U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                                          U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                                          U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                                          U256C::from(0)).await);
+    require!(eth_token_contract.ft_balance_of(&taker_account).await?.0 == taker_eth_before);
+
+    // storage paid by storage balance, quote deposited before, base transferred with the request
+    check_success(gridbot_contract.storage_deposit(&maker_account).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    let storage_before = gridbot_contract.storage_balance_of(&maker_id).await?.unwrap();
+    let maker_eth_before = eth_token_contract.ft_balance_of(&maker_account).await?.0;
+    check_success(gridbot_contract.create_bot_by_transfer(&eth_token_contract, &maker_account, 10000000000000000000000, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                                          U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                                          U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                                          U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                                          U256C::from(0)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    let bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    // only the amount locked by bot is taken
+    require!(eth_token_contract.ft_balance_of(&maker_account).await?.0 == maker_eth_before - bot.total_base_amount.as_u128());
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 0);
+
+    // bot storage is charged to storage account, not drawn from it
+    require!(bot.storage_used_bytes > 0);
+    let storage_after_create = gridbot_contract.storage_balance_of(&maker_id).await?.unwrap();
+    require!(storage_after_create.total.0 == storage_before.total.0);
+    require!(storage_after_create.available.0 < storage_before.available.0);
+    // released to storage account when closed
+    check_success(gridbot_contract.close_bot(&maker_account, next_bot_id.clone()).await);
+    let storage_after_close = gridbot_contract.storage_balance_of(&maker_id).await?.unwrap();
+    require!(storage_after_close.total.0 == storage_before.total.0);
+    require!(storage_after_close.available.0 > storage_after_create.available.0);
+
+    Ok(())
+}
@@ -6,12 +6,141 @@ use near_units::parse_near;
 use workspaces::network::Testnet;
 use workspaces::{Account, Worker};
 use workspaces::result::ExecutionFinalResult;
-use grid::{GridBotContract, GridType, Order, OrderKeyInfo, TakeRequest, U256C};
+use grid::{GridBotContract, GridType, Order, OrderKeyInfo, RequestOrder, TakeRequest, U256C};
 use common::*;
 use crate::workspace_env::*;
 
 mod workspace_env;
 
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+#[tokio::test]
+async fn take_best() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+    check_success(eth_token_contract.ft_mint(&taker_account, U128::from(20000000000000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    // register pair
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    // deposit
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &taker_account, 1000000000).await);
+
+    // create bot
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(7000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    let taker_account_id = AccountId::from_str(taker_account.id()).expect("Invalid AccountId");
+    check_success(gridbot_contract.set_market_user(&owner, &taker_account_id, true).await);
+
+    // sell ETH, the best buy orders:
+    // level 14: 100000000, 2000000000 + 10000000 * 14=2140000000
+    // level 13: 100000000, 2000000000 + 10000000 * 13=2130000000
+    // level 12: 100000000, 2000000000 + 10000000 * 12=2120000000, lower than taker's price
+    let take_order = RequestOrder {
+        token_sell: eth_token_contract.get_account_id(),
+        token_buy: usdc_token_contract.get_account_id(),
+        amount_sell: U128::from(300000000 as u128),
+        amount_buy: U128::from(6390000000 as u128),
+        fill_buy_or_sell: false,
+        filled: U128::from(0),
+    };
+    check_success(gridbot_contract.take_best(&taker_account, pair_id.clone(), &take_order, 10).await);
+
+    let order_result = gridbot_contract.query_order(next_bot_id.clone(), true, 14).await?.unwrap();
+    require!(order_result.order.filled == order_result.order.amount_buy);
+    let order_result = gridbot_contract.query_order(next_bot_id.clone(), true, 13).await?.unwrap();
+    require!(order_result.order.filled == order_result.order.amount_buy);
+    // level 12 is still a virtual order
+    let order_result = gridbot_contract.query_order(next_bot_id.clone(), true, 12).await?.unwrap();
+    require!(!order_result.flag);
+
+    // 200000000 sold, 4270000000 * (1000000 - 500)/1000000=4267865000
+    let taker_eth_balance = gridbot_contract.query_user_balance(&taker_account_id, eth_token_contract.get_account_id()).await?.unwrap();
+    require!(taker_eth_balance.0 == (800000000 as u128));
+    let taker_usdc_balance = gridbot_contract.query_user_balance(&taker_account_id, usdc_token_contract.get_account_id()).await?.unwrap();
+    require!(taker_usdc_balance.0 == (4267865000 as u128));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn take_price_deviation() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+    check_success(eth_token_contract.ft_mint(&taker_account, U128::from(20000000000000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    // pair price = 21
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2100, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &taker_account, 1000000000).await);
+
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(21000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    let taker_account_id = AccountId::from_str(taker_account.id()).expect("Invalid AccountId");
+    check_success(gridbot_contract.set_market_user(&owner, &taker_account_id, true).await);
+    check_failure(gridbot_contract.set_pair_max_deviation_rate(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), 100).await, "TOKEN_DECIMALS_NOT_SET");
+    // same decimals, book price = oracle pair price
+    check_success(gridbot_contract.set_pair_token_decimals(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), 6, 6).await);
+    // 1%
+    check_success(gridbot_contract.set_pair_max_deviation_rate(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), 100).await);
+
+    // sell ETH to level 14, price 21.4
+    let take_order = RequestOrder {
+        token_sell: eth_token_contract.get_account_id(),
+        token_buy: usdc_token_contract.get_account_id(),
+        amount_sell: U128::from(100000000 as u128),
+        amount_buy: U128::from(2140000000 as u128),
+        fill_buy_or_sell: false,
+        filled: U128::from(0),
+    };
+    // no cached price, take is halted
+    check_success(gridbot_contract.set_pair_halt_on_stale_price(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), true).await);
+    let result = gridbot_contract.take_best(&taker_account, pair_id.clone(), &take_order, 1).await?;
+    require!(result.is_failure());
+
+    // 21.4 is 1.9% away from 21
+    check_success(gridbot_contract.refresh_prices(&taker_account, vec![pair_id.clone()]).await);
+    let result = gridbot_contract.take_best(&taker_account, pair_id.clone(), &take_order, 1).await?;
+    require!(result.is_failure());
+
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2140, 0, -2, None).await);
+    check_success(gridbot_contract.refresh_prices(&taker_account, vec![pair_id.clone()]).await);
+    check_success(gridbot_contract.take_best(&taker_account, pair_id.clone(), &take_order, 1).await);
+    let order_result = gridbot_contract.query_order(next_bot_id.clone(), true, 14).await?.unwrap();
+    require!(order_result.order.filled == order_result.order.amount_buy);
+
+    Ok(())
+}
+
 // #[tokio::test]
 // async fn take() -> Result<(), workspaces::error::Error> {
 //     let (worker, owner, maker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
@@ -0,0 +1,246 @@
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{GridType, OracleProvider, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+#[tokio::test]
+async fn trigger_bot_with_mock_oracle() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    // pair price = 2000 * 10^-2 / (100000000 * 10^-8) = 20
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+
+    // trigger when price rises to 21
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    require!(!gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    // oracle returns nothing, INVALID_PAIR_PRICE_LENGTH
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::ReturnNone).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "INVALID_PAIR_PRICE_LENGTH");
+
+    // oracle panics, INVALID_PAIR_PRICE_LENGTH
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::Panic).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "INVALID_PAIR_PRICE_LENGTH");
+
+    // price reached, but stale
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::Normal).await);
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 0, -2, Some(get_time_stamp() as i64 - 3600)).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "INVALID_PRICE");
+
+    // fresh price not reached
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "CAN_NOT_TRIGGER");
+    require!(!gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    // fresh price reached
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 0, -2, None).await);
+    check_success(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await);
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn trigger_bot_with_cached_price() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    check_success(gridbot_contract.set_pair_use_cached_price(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), true).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    require!(gridbot_contract.query_pair_price(pair_id.clone()).await?.is_none());
+
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // cache price 22
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 0, -2, None).await);
+    check_success(gridbot_contract.refresh_prices(&taker_account, vec![pair_id.clone()]).await);
+    let pair_price = gridbot_contract.query_pair_price(pair_id.clone()).await?.unwrap();
+    require!(pair_price.price.0 == 22000000000000000000 as u128);
+    require!(pair_price.twap_price.0 == 22000000000000000000 as u128);
+
+    // triggered by cached price, oracle is not called
+    check_success(mock_pyth.set_mode(&owner, MockPythMode::Panic).await);
+    check_success(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await);
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn trigger_bot_with_price_oracle_fallback() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    // the mock serves both pyth get_price and priceoracle get_price_data
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+    check_success(gridbot_contract.set_price_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    // priceoracle can't be primary before token decimals are set
+    check_failure(gridbot_contract.set_pair_oracle_provider(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), OracleProvider::PriceOracle).await, "TOKEN_DECIMALS_NOT_SET");
+
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // 1 smallest unit of 6 decimals: eth = 2200 / 10^8 usd, usdc = 100000000 / 10^14 usd, pair price = 22
+    check_success(mock_pyth.set_asset_price(&owner, &eth_token_contract.get_account_id(), 2200, 8).await);
+    check_success(mock_pyth.set_asset_price(&owner, &usdc_token_contract.get_account_id(), 100000000, 14).await);
+    // pyth has no eth price, no fallback before token decimals are set
+    check_success(mock_pyth.remove_price(&owner, eth_oracle_id).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "INVALID_PAIR_PRICE_LENGTH");
+    require!(!gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    // triggered by priceoracle price
+    check_success(gridbot_contract.set_pair_token_decimals(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), 6, 6).await);
+    check_success(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await);
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn trigger_bot_pays_keeper_reward() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    // budget less than one reward, create_bot_error and assets are withdrawn
+    let keeper_reward = 10000000000000000000000 as u128;
+    let result = gridbot_contract.create_bot_with_keeper_reward(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128), keeper_reward, keeper_reward - 1).await?;
+    require!(result.logs().iter().any(|log| log.contains("INVALID_KEEPER_REWARD")));
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    // budget of three rewards
+    check_success(gridbot_contract.create_bot_with_keeper_reward(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128), keeper_reward, keeper_reward * 3).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().keeper_reward_balance.as_u128() == keeper_reward * 3);
+
+    // the caller of trigger_bot is paid
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 0, -2, None).await);
+    let result = gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await?;
+    require!(result.is_success());
+    let keeper_reward_log = format!("\"keeper\":\"{}\"", taker_account.id());
+    require!(result.logs().iter().any(|log| log.contains("keeper_reward") && log.contains(&keeper_reward_log)));
+    let bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(bot.active);
+    require!(bot.keeper_reward_balance.as_u128() == keeper_reward * 2);
+
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn trigger_bot_with_wide_conf() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let mock_pyth = deploy_mock_pyth(&worker, &owner).await?;
+    check_success(gridbot_contract.set_oracle(&owner, &mock_pyth.get_account_id()).await);
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), true, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    // conf must be within 1% of price
+    check_success(gridbot_contract.set_pair_max_conf_rate(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), 100).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2000, 0, -2, None).await);
+    check_success(mock_pyth.set_price(&owner, usdc_oracle_id, 100000000, 0, -8, None).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 100, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(21000000000000000000 as u128), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(20000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // price reached, but conf 100 is wider than 1% of 2200
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 100, -2, None).await);
+    check_failure(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await, "INVALID_PRICE");
+    require!(!gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    // conf 10 is within 1% of 2200
+    check_success(mock_pyth.set_price(&owner, eth_oracle_id, 2200, 10, -2, None).await);
+    check_success(gridbot_contract.trigger_bot(&taker_account, next_bot_id.clone()).await);
+    require!(gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap().active);
+
+    Ok(())
+}
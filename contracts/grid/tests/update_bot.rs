@@ -0,0 +1,153 @@
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::{GridType, U256C};
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
+    return format!("{}:{}", base_token.clone().to_string(), quote_token.clone().to_string());
+}
+
+#[tokio::test]
+async fn update_bot() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(7000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // extend expiry and rename
+    let new_valid_until_time = get_time_stamp() * 1000 + 7200000000;
+    check_success(gridbot_contract.update_bot(&maker_account, next_bot_id.clone(), Some("renamed".to_string()), None, None, None, Some(U128::from(new_valid_until_time as u128)), None, None).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.name == "renamed".to_string());
+    require!(grid_bot.valid_until_time == U256C::from(new_valid_until_time));
+
+    // pair not require oracle, can't set take profit price
+    let result = gridbot_contract.update_bot(&maker_account, next_bot_id.clone(), None, None, Some(U128::from(8000000000000000000 as u128)), None, None, None, None).await?;
+    require!(result.is_failure());
+    // expired time
+    let result = gridbot_contract.update_bot(&maker_account, next_bot_id.clone(), None, None, None, None, Some(U128::from(1 as u128)), None, None).await?;
+    require!(result.is_failure());
+    // only bot's owner
+    let result = gridbot_contract.update_bot(&taker_account, next_bot_id.clone(), Some("taker".to_string()), None, None, None, None, None, None).await?;
+    require!(result.is_failure());
+
+    // pair not require oracle, can't use trailing stop
+    let result = gridbot_contract.update_bot(&maker_account, next_bot_id.clone(), None, None, None, None, None, None, Some(500)).await?;
+    require!(result.is_failure());
+
+    // trailing mode, nothing filled, can't move grid
+    check_success(gridbot_contract.update_bot(&maker_account, next_bot_id.clone(), None, None, None, None, None, Some(true), None).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.trailing);
+    let result = gridbot_contract.trail_bot(&taker_account, next_bot_id.clone()).await?;
+    require!(result.is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn change_bot_assets() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(7000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    let total_base_amount = grid_bot.total_base_amount.clone();
+    let total_quote_amount = grid_bot.total_quote_amount.clone();
+
+    // double the bot, quote is the max amount
+    check_success(gridbot_contract.increase_bot_assets(&maker_account, next_bot_id.clone(), U128::from(total_base_amount.as_u128()), U128::from(total_quote_amount.as_u128() * 2)).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.total_base_amount == total_base_amount * U256C::from(2));
+    require!(grid_bot.total_quote_amount == total_quote_amount * U256C::from(2));
+    require!(grid_bot.first_base_amount == U256C::from(200000000));
+    require!(grid_bot.last_quote_amount == U256C::from(6000000000 as u128));
+
+    // back to half
+    check_success(gridbot_contract.decrease_bot_assets(&maker_account, next_bot_id.clone(), U128::from(total_base_amount.as_u128()), U128::from(total_quote_amount.as_u128())).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.total_base_amount == total_base_amount);
+    require!(grid_bot.total_quote_amount == total_quote_amount);
+    require!(grid_bot.first_base_amount == U256C::from(100000000));
+
+    // can't remove all assets
+    let result = gridbot_contract.decrease_bot_assets(&maker_account, next_bot_id.clone(), U128::from(total_base_amount.as_u128()), U128::from(total_quote_amount.as_u128())).await?;
+    require!(result.is_failure());
+    // only bot's owner
+    let result = gridbot_contract.increase_bot_assets(&taker_account, next_bot_id.clone(), U128::from(total_base_amount.as_u128()), U128::from(total_quote_amount.as_u128())).await?;
+    require!(result.is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pause_and_resume_bot() -> Result<(), workspaces::error::Error> {
+    let (worker, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(usdc_token_contract.ft_mint(&maker_account, U128::from(100000000000000 as u128).into()).await);
+
+    let eth_oracle_id = "27e867f0f4f61076456d1a73b14c7edc1cf5cef4f4d6193a33424288f11bd0f4";
+    let usdc_oracle_id = "1fc18861232290221461220bd4e2acd1dcdfbc89c84092c93c18bdc7756c1588";
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, eth_oracle_id.to_string(), usdc_oracle_id.to_string()).await);
+    let pair_id = get_pair_key(&(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()));
+
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+    check_success(gridbot_contract.deposit(&usdc_token_contract, &maker_account, 100000000000000).await);
+    check_success(gridbot_contract.create_bot(&maker_account, pair_id.clone(), 9999, GridType::EqOffset, 0,
+                                              U256C::from(10000000), U256C::from(100000000), U256C::from(2000000000),
+                                              U256C::from(100000000), U256C::from(3000000000 as u128), true, 10, 15,
+                                              U256C::from(0), U256C::from(0), U256C::from(0), U256C::from(get_time_stamp() * 1000 + 3600000000),
+                                              U256C::from(7000000000000000000 as u128)).await);
+    let next_bot_id = format!("GRID:{}", "1".to_string());
+
+    // only bot's owner
+    let result = gridbot_contract.pause_bot(&taker_account, next_bot_id.clone()).await?;
+    require!(result.is_failure());
+
+    check_success(gridbot_contract.pause_bot(&maker_account, next_bot_id.clone()).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(grid_bot.paused);
+    require!(gridbot_contract.query_order(next_bot_id.clone(), true, 0).await.is_err());
+    // paused twice
+    let result = gridbot_contract.pause_bot(&maker_account, next_bot_id.clone()).await?;
+    require!(result.is_failure());
+
+    check_success(gridbot_contract.resume_bot(&maker_account, next_bot_id.clone()).await);
+    let grid_bot = gridbot_contract.query_bot(next_bot_id.clone()).await?.unwrap();
+    require!(!grid_bot.paused);
+    require!(gridbot_contract.query_order(next_bot_id.clone(), true, 0).await?.is_some());
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::str::FromStr;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, log, require};
+use grid::U256C;
+use common::*;
+use crate::workspace_env::*;
+
+mod workspace_env;
+
+#[tokio::test]
+async fn withdraw_to() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let taker_id = AccountId::from_str(taker_account.id()).expect("Invalid AccountId");
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+
+    // more than balance
+    let result = gridbot_contract.withdraw_to(&maker_account, eth_token_contract.get_account_id(), U128::from(10000000000000000000001), taker_id.clone(), None).await?;
+    require!(result.is_failure());
+
+    // part of balance to another receiver
+    let taker_eth_before = eth_token_contract.ft_balance_of(&taker_account).await?.0;
+    let result = gridbot_contract.withdraw_to(&maker_account, eth_token_contract.get_account_id(), U128::from(1000000000000000000000), taker_id.clone(), None).await?;
+    let withdrawn = result.json::<U128>().unwrap();
+    log!("withdraw_to withdrawn:{}", withdrawn.0);
+    require!(withdrawn.0 == 1000000000000000000000);
+    require!(eth_token_contract.ft_balance_of(&taker_account).await?.0 == taker_eth_before + 1000000000000000000000);
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 9000000000000000000000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_withdrawals() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+
+    // maker isn't registered in token, payout fails
+    check_success(eth_token_contract.ft_storage_unregister(&maker_account).await);
+    check_success(gridbot_contract.withdraw(&maker_account, eth_token_contract.get_account_id()).await);
+    let pending_withdrawals = gridbot_contract.query_pending_withdrawals(&maker_id).await?;
+    require!(pending_withdrawals.len() == 1);
+    require!(pending_withdrawals[0].amount.0 == 10000000000000000000000);
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 0);
+
+    // anyone can retry after registered
+    check_success(eth_token_contract.ft_storage_deposit(&maker_id).await);
+    check_success(gridbot_contract.retry_withdrawals(&taker_account, &maker_id).await);
+    require!(gridbot_contract.query_pending_withdrawals(&maker_id).await?.is_empty());
+    require!(eth_token_contract.ft_balance_of(&maker_account).await?.0 == 10000000000000000000000);
+
+    let result = gridbot_contract.retry_withdrawals(&taker_account, &maker_id).await?;
+    require!(result.is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn claim_pending_to_balance() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, taker_account, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let taker_id = AccountId::from_str(taker_account.id()).expect("Invalid AccountId");
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+
+    // maker left the token, payout to maker fails
+    check_success(eth_token_contract.ft_storage_unregister(&maker_account).await);
+    check_success(gridbot_contract.withdraw(&maker_account, eth_token_contract.get_account_id()).await);
+    require!(gridbot_contract.query_pending_withdrawals(&maker_id).await?.len() == 1);
+    require!(gridbot_contract.query_token_accounting(eth_token_contract.get_account_id()).await?.pending_withdrawal.0 == 10000000000000000000000);
+
+    // only the user's own pending withdrawal can be claimed
+    let result = gridbot_contract.claim_pending_to_balance(&taker_account, eth_token_contract.get_account_id()).await?;
+    require!(result.is_failure());
+    check_success(gridbot_contract.claim_pending_to_balance(&maker_account, eth_token_contract.get_account_id()).await);
+    require!(gridbot_contract.query_pending_withdrawals(&maker_id).await?.is_empty());
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 10000000000000000000000);
+    let accounting = gridbot_contract.query_token_accounting(eth_token_contract.get_account_id()).await?;
+    require!(accounting.pending_withdrawal.0 == 0);
+    require!(accounting.free.0 == 10000000000000000000000);
+
+    // withdraw to another receiver
+    check_success(gridbot_contract.withdraw_to(&maker_account, eth_token_contract.get_account_id(), U128::from(10000000000000000000000), taker_id.clone(), None).await);
+    require!(gridbot_contract.query_user_balance(&maker_id, eth_token_contract.get_account_id()).await?.unwrap().0 == 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn token_accounting() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, _, gridbot_contract, eth_token_contract, usdc_token_contract) = create_contract().await?;
+
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(10000000000000000000000 as u128).into()).await);
+    check_success(gridbot_contract.register_pair(&owner, &(eth_token_contract.get_account_id()), &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+    check_success(gridbot_contract.deposit(&eth_token_contract, &maker_account, 10000000000000000000000).await);
+
+    let accounting = gridbot_contract.query_token_accounting(eth_token_contract.get_account_id()).await?;
+    require!(accounting.free.0 == 10000000000000000000000);
+    require!(accounting.recorded.0 == 10000000000000000000000);
+    require!(accounting.reconcile_time == 0);
+
+    // someone transfers token to contract directly
+    check_success(eth_token_contract.ft_mint(&maker_account, U128::from(1000 as u128).into()).await);
+    check_success(eth_token_contract.ft_transfer(&maker_account, gridbot_contract.0.as_account(), 1000).await);
+
+    // only owner
+    let result = gridbot_contract.reconcile(&maker_account, eth_token_contract.get_account_id()).await?;
+    require!(result.is_failure());
+    check_success(gridbot_contract.reconcile(&owner, eth_token_contract.get_account_id()).await);
+    let accounting = gridbot_contract.query_token_accounting(eth_token_contract.get_account_id()).await?;
+    require!(accounting.reconciled_balance.0 == 10000000000000001000000);
+    require!(accounting.unowned.0 == 1000);
+
+    Ok(())
+}
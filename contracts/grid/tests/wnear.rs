@@ -120,3 +120,24 @@ pub fn get_pair_key(base_token: &AccountId, quote_token: &AccountId) -> String {
 //
 //     Ok(())
 // }
+
+#[tokio::test]
+async fn deposit_near() -> Result<(), workspaces::error::Error> {
+    let (_, owner, maker_account, _, gridbot_contract, _, usdc_token_contract) = create_contract().await?;
+    let maker_id = AccountId::from_str(maker_account.id()).expect("Invalid AccountId");
+    let wnear = AccountId::from_str("wrap.testnet").expect("Invalid AccountId");
+
+    check_success(gridbot_contract.register_pair(&owner, &wnear, &(usdc_token_contract.get_account_id()), U256C::from(1000), U256C::from(1000), false, "".to_string(), "".to_string()).await);
+
+    // not registered
+    let result = gridbot_contract.deposit_near(&maker_account, parse_near!("1 N")).await?;
+    require!(result.is_failure());
+
+    check_success(gridbot_contract.storage_deposit(&maker_account).await);
+    check_success(gridbot_contract.deposit_near(&maker_account, parse_near!("1 N")).await);
+    let wnear_balance = gridbot_contract.query_user_balance(&maker_id, wnear.clone()).await?.unwrap();
+    log!("wnear balance:{}", wnear_balance.0);
+    require!(wnear_balance.0 == parse_near!("1 N"));
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use near_sdk::AccountId;
+use near_contract_standards::storage_management::StorageBalance;
 use near_units::parse_near;
 use near_sdk::json_types::U128;
 use serde_json::json;
 use workspaces::{Account, Contract};
 use workspaces::result::ExecutionFinalResult;
-use grid::{GridBot, GridType, Order, RequestOrder, OrderKeyInfo, OrderResult, U256C};
+use grid::{GridBot, GridType, Order, RequestOrder, OrderKeyInfo, OrderResult, PairOutput, LevelOrdersOutput, DepthOutput, OrderSide, MultiToken, OracleProvider, PairPrice, PendingWithdrawal, RequestGridLevel, TokenAccountingOutput, U256C};
 use crate::*;
 
 pub struct GridBotHelper(pub Contract);
@@ -37,6 +38,54 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn storage_withdraw(&self, caller: &Account, amount: Option<U128>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start storage_withdraw");
+        caller
+            .call(self.0.id(), "storage_withdraw")
+            .args_json(json!({
+                "amount": amount,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn storage_unregister(&self, caller: &Account) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start storage_unregister");
+        caller
+            .call(self.0.id(), "storage_unregister")
+            .args_json(json!({
+                "force": Option::<bool>::None,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn storage_balance_of(&self, account_id: &AccountId) -> Result<Option<StorageBalance>, workspaces::error::Error> {
+        log!("start storage_balance_of");
+        self.0
+            .call("storage_balance_of")
+            .args_json(json!({
+                "account_id": account_id,
+            }))
+            .view()
+            .await?
+            .json::<Option<StorageBalance>>()
+    }
+
+    pub async fn deposit_near(&self, caller: &Account, amount: u128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start deposit_near");
+        caller
+            .call(self.0.id(), "deposit_near")
+            .gas(300_000_000_000_000)
+            .deposit(amount)
+            .transact()
+            .await
+    }
+
     pub async fn deposit(&self, token_contract: &FtContractHelper, caller: &Account, amount: u128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start deposit");
         return token_contract.ft_transfer_call(caller, &(AccountId::from_str(self.0.id()).expect("Invalid AccountId")), amount, "".to_string()).await;
@@ -61,6 +110,19 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn set_expired_close_reward_rate(&self, caller: &Account, new_expired_close_reward_rate: u128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_expired_close_reward_rate");
+        caller
+            .call(self.0.id(), "set_expired_close_reward_rate")
+            .args_json(json!({
+                "new_expired_close_reward_rate": U128::from(new_expired_close_reward_rate),
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
     pub async fn set_refer_fee_rate(&self, caller: &Account, new_refer_fee_rate: Vec<u32>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start set_refer_fee_rate");
         caller
@@ -121,6 +183,102 @@ impl GridBotHelper {
             .await
     }
 
+    /// keeper_reward_balance is prepaid in attached NEAR
+    pub async fn create_bot_with_keeper_reward(&self, caller: &Account, pair_id: String, slippage: u16, grid_type: GridType, grid_rate: u16, grid_offset: U256C, first_base_amount: U256C, first_quote_amount: U256C,
+                            last_base_amount: U256C, last_quote_amount: U256C, fill_base_or_quote: bool, grid_sell_count: u16, grid_buy_count: u16,
+                            trigger_price: U256C, take_profit_price: U256C, stop_loss_price: U256C, valid_until_time: U256C,
+                            entry_price: U256C, keeper_reward: u128, keeper_reward_balance: u128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start create_bot_with_keeper_reward");
+        caller
+            .call(self.0.id(), "create_bot")
+            .args_json(json!({
+                "name": "testname",
+                "pair_id": pair_id,
+                "slippage": slippage,
+                "grid_type": grid_type,
+                "grid_rate": grid_rate,
+                "grid_offset": U128::from(grid_offset.as_u128()),
+                "first_base_amount": U128::from(first_base_amount.as_u128()),
+                "first_quote_amount": U128::from(first_quote_amount.as_u128()),
+                "last_base_amount": U128::from(last_base_amount.as_u128()),
+                "last_quote_amount": U128::from(last_quote_amount.as_u128()),
+                "fill_base_or_quote": fill_base_or_quote,
+                "grid_sell_count": grid_sell_count,
+                "grid_buy_count": grid_buy_count,
+                "trigger_price": U128::from(trigger_price.as_u128()),
+                "take_profit_price": U128::from(take_profit_price.as_u128()),
+                "stop_loss_price": U128::from(stop_loss_price.as_u128()),
+                "valid_until_time": U128::from(valid_until_time.as_u128()),
+                "entry_price": U128::from(entry_price.as_u128()),
+                "keeper_reward": U128::from(keeper_reward),
+                "keeper_reward_balance": U128::from(keeper_reward_balance),
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1000_000_000_000_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn create_bot_by_transfer(&self, token_contract: &FtContractHelper, caller: &Account, amount: u128, pair_id: String, slippage: u16, grid_type: GridType, grid_rate: u16, grid_offset: U256C, first_base_amount: U256C, first_quote_amount: U256C,
+                            last_base_amount: U256C, last_quote_amount: U256C, fill_base_or_quote: bool, grid_sell_count: u16, grid_buy_count: u16,
+                            trigger_price: U256C, take_profit_price: U256C, stop_loss_price: U256C, valid_until_time: U256C,
+                            entry_price: U256C) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start create_bot_by_transfer");
+        let msg = json!({
+            "name": "testname",
+            "pair_id": pair_id,
+            "slippage": slippage,
+            "grid_type": grid_type,
+            "grid_rate": grid_rate,
+            "grid_offset": U128::from(grid_offset.as_u128()),
+            "first_base_amount": U128::from(first_base_amount.as_u128()),
+            "first_quote_amount": U128::from(first_quote_amount.as_u128()),
+            "last_base_amount": U128::from(last_base_amount.as_u128()),
+            "last_quote_amount": U128::from(last_quote_amount.as_u128()),
+            "fill_base_or_quote": fill_base_or_quote,
+            "grid_sell_count": grid_sell_count,
+            "grid_buy_count": grid_buy_count,
+            "trigger_price": U128::from(trigger_price.as_u128()),
+            "take_profit_price": U128::from(take_profit_price.as_u128()),
+            "stop_loss_price": U128::from(stop_loss_price.as_u128()),
+            "valid_until_time": U128::from(valid_until_time.as_u128()),
+            "entry_price": U128::from(entry_price.as_u128()),
+        });
+        return token_contract.ft_transfer_call(caller, &(AccountId::from_str(self.0.id()).expect("Invalid AccountId")), amount, msg.to_string()).await;
+    }
+
+    pub async fn create_custom_bot(&self, caller: &Account, pair_id: String, fill_base_or_quote: bool, grid_sell_count: u16, grid_buy_count: u16,
+                                   custom_levels: Vec<RequestGridLevel>, valid_until_time: U256C) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start create_custom_bot");
+        caller
+            .call(self.0.id(), "create_bot")
+            .args_json(json!({
+                "name": "testname",
+                "pair_id": pair_id,
+                "slippage": 9999,
+                "grid_type": GridType::Custom,
+                "grid_rate": 0,
+                "grid_offset": U128::from(0),
+                "first_base_amount": U128::from(0),
+                "first_quote_amount": U128::from(0),
+                "last_base_amount": U128::from(0),
+                "last_quote_amount": U128::from(0),
+                "fill_base_or_quote": fill_base_or_quote,
+                "grid_sell_count": grid_sell_count,
+                "grid_buy_count": grid_buy_count,
+                "trigger_price": U128::from(0),
+                "take_profit_price": U128::from(0),
+                "stop_loss_price": U128::from(0),
+                "valid_until_time": U128::from(valid_until_time.as_u128()),
+                "entry_price": U128::from(0),
+                "custom_levels": custom_levels,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1000_000_000_000_000_000_000_000)
+            .transact()
+            .await
+    }
+
     pub async fn create_bot_with_near(&self, caller: &Account, pair_id: String, slippage: u16, grid_type: GridType, grid_rate: u16, grid_offset: U256C, first_base_amount: U256C, first_quote_amount: U256C,
                             last_base_amount: U256C, last_quote_amount: U256C, fill_base_or_quote: bool, grid_sell_count: u16, grid_buy_count: u16,
                             trigger_price: U256C, take_profit_price: U256C, stop_loss_price: U256C, valid_until_time: U256C,
@@ -168,6 +326,21 @@ impl GridBotHelper {
     //         .await
     // }
 
+    pub async fn take_best(&self, caller: &Account, pair_id: String, take_order: &RequestOrder, max_makers: u16) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start take_best");
+        caller
+            .call(self.0.id(), "take_best")
+            .args_json(json!({
+                "pair_id": pair_id,
+                "take_order": take_order,
+                "max_makers": max_makers,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
     pub async fn claim(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start claim");
         caller
@@ -194,6 +367,132 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn update_bot(&self, caller: &Account, bot_id: String, name: Option<String>, trigger_price: Option<U128>, take_profit_price: Option<U128>,
+                            stop_loss_price: Option<U128>, valid_until_time: Option<U128>, trailing: Option<bool>,
+                            trailing_stop_rate: Option<u16>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start update_bot");
+        caller
+            .call(self.0.id(), "update_bot")
+            .args_json(json!({
+                "bot_id": bot_id,
+                "name": name,
+                "trigger_price": trigger_price,
+                "take_profit_price": take_profit_price,
+                "stop_loss_price": stop_loss_price,
+                "valid_until_time": valid_until_time,
+                "trailing": trailing,
+                "trailing_stop_rate": trailing_stop_rate,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn increase_bot_assets(&self, caller: &Account, bot_id: String, base_amount: U128, quote_amount: U128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start increase_bot_assets");
+        caller
+            .call(self.0.id(), "increase_bot_assets")
+            .args_json(json!({
+                "bot_id": bot_id,
+                "base_amount": base_amount,
+                "quote_amount": quote_amount,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn decrease_bot_assets(&self, caller: &Account, bot_id: String, base_amount: U128, quote_amount: U128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start decrease_bot_assets");
+        caller
+            .call(self.0.id(), "decrease_bot_assets")
+            .args_json(json!({
+                "bot_id": bot_id,
+                "base_amount": base_amount,
+                "quote_amount": quote_amount,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn pause_bot(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start pause_bot");
+        caller
+            .call(self.0.id(), "pause_bot")
+            .args_json(json!({
+                "bot_id": bot_id,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn resume_bot(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start resume_bot");
+        caller
+            .call(self.0.id(), "resume_bot")
+            .args_json(json!({
+                "bot_id": bot_id,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn close_expired_bot(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start close_expired_bot");
+        caller
+            .call(self.0.id(), "close_expired_bot")
+            .args_json(json!({
+                "bot_id": bot_id,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn update_high_water_price(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start update_high_water_price");
+        caller
+            .call(self.0.id(), "update_high_water_price")
+            .args_json(json!({
+                "bot_id": bot_id,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn trail_bot(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start trail_bot");
+        caller
+            .call(self.0.id(), "trail_bot")
+            .args_json(json!({
+                "bot_id": bot_id,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn refresh_prices(&self, caller: &Account, pair_ids: Vec<String>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start refresh_prices");
+        caller
+            .call(self.0.id(), "refresh_prices")
+            .args_json(json!({
+                "pair_ids": pair_ids,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
     pub async fn trigger_bot(&self, caller: &Account, bot_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start trigger_bot");
         caller
@@ -220,7 +519,91 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn mt_on_transfer(&self, caller: &Account, sender_id: &AccountId, token_ids: Vec<String>, amounts: Vec<U128>, msg: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start mt_on_transfer");
+        caller
+            .call(self.0.id(), "mt_on_transfer")
+            .args_json(json!({
+                "sender_id": sender_id,
+                "previous_owner_ids": vec![sender_id.clone(); token_ids.len()],
+                "token_ids": token_ids,
+                "amounts": amounts,
+                "msg": msg,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn withdraw_to(&self, caller: &Account, token: AccountId, amount: U128, receiver_id: AccountId, msg: Option<String>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start withdraw_to");
+        caller
+            .call(self.0.id(), "withdraw_to")
+            .args_json(json!({
+                "token": token,
+                "amount": amount,
+                "receiver_id": receiver_id,
+                "msg": msg,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn retry_withdrawals(&self, caller: &Account, user: &AccountId) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start retry_withdrawals");
+        caller
+            .call(self.0.id(), "retry_withdrawals")
+            .args_json(json!({
+                "user": user,
+            }))
+            .gas(300_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn claim_pending_to_balance(&self, caller: &Account, token: AccountId) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start claim_pending_to_balance");
+        caller
+            .call(self.0.id(), "claim_pending_to_balance")
+            .args_json(json!({
+                "token": token,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
     // ####################################### Owner
+    pub async fn register_multi_token(&self, caller: &Account, contract_id: &AccountId, token_id: String) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start register_multi_token");
+        caller
+            .call(self.0.id(), "register_multi_token")
+            .args_json(json!({
+                "contract_id": contract_id,
+                "token_id": token_id,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn reconcile(&self, caller: &Account, token: AccountId) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start reconcile");
+        caller
+            .call(self.0.id(), "reconcile")
+            .args_json(json!({
+                "token": token,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
     pub async fn withdraw_protocol_fee(&self, caller: &Account, token: AccountId, to_user: AccountId, amount: U128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start withdraw_protocol_fee");
         caller
@@ -285,6 +668,20 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn set_market_user(&self, caller: &Account, market_user: &AccountId, enable: bool) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_market_user");
+        caller
+            .call(self.0.id(), "set_market_user")
+            .args_json(json!({
+                "market_user": market_user,
+                "enable": enable,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
     pub async fn set_min_deposit(&self, caller: &Account, token: AccountId, min_deposit: U256C) -> Result<ExecutionFinalResult, workspaces::error::Error> {
         log!("start set_min_deposit");
         caller
@@ -299,6 +696,137 @@ impl GridBotHelper {
             .await
     }
 
+    pub async fn storage_deposit_for_token(&self, caller: &Account, token: &AccountId, storage_fee: u128) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start storage_deposit_for_token");
+        caller
+            .call(self.0.id(), "storage_deposit_for_token")
+            .args_json(json!({
+                "token": token,
+                "storage_fee": U128::from(storage_fee),
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(storage_fee)
+            .transact()
+            .await
+    }
+
+    pub async fn set_oracle(&self, caller: &Account, new_oracle: &AccountId) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_oracle");
+        caller
+            .call(self.0.id(), "set_oracle")
+            .args_json(json!({
+                "new_oracle": new_oracle,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_price_oracle(&self, caller: &Account, new_price_oracle: &AccountId) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_price_oracle");
+        caller
+            .call(self.0.id(), "set_price_oracle")
+            .args_json(json!({
+                "new_price_oracle": new_price_oracle,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_oracle_provider(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, oracle_provider: OracleProvider) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_oracle_provider");
+        caller
+            .call(self.0.id(), "set_pair_oracle_provider")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "oracle_provider": oracle_provider,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_max_deviation_rate(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, max_deviation_rate: u16) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_max_deviation_rate");
+        caller
+            .call(self.0.id(), "set_pair_max_deviation_rate")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "max_deviation_rate": max_deviation_rate,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_halt_on_stale_price(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, halt_on_stale_price: bool) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_halt_on_stale_price");
+        caller
+            .call(self.0.id(), "set_pair_halt_on_stale_price")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "halt_on_stale_price": halt_on_stale_price,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_use_cached_price(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, use_cached_price: bool) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_use_cached_price");
+        caller
+            .call(self.0.id(), "set_pair_use_cached_price")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "use_cached_price": use_cached_price,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_token_decimals(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, base_token_decimals: u8, quote_token_decimals: u8) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_token_decimals");
+        caller
+            .call(self.0.id(), "set_pair_token_decimals")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "base_token_decimals": base_token_decimals,
+                "quote_token_decimals": quote_token_decimals,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
+    pub async fn set_pair_max_conf_rate(&self, caller: &Account, base_token: &AccountId, quote_token: &AccountId, max_conf_rate: u16) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_pair_max_conf_rate");
+        caller
+            .call(self.0.id(), "set_pair_max_conf_rate")
+            .args_json(json!({
+                "base_token": base_token,
+                "quote_token": quote_token,
+                "max_conf_rate": max_conf_rate,
+            }))
+            .gas(300_000_000_000_000)
+            .deposit(1)
+            .transact()
+            .await
+    }
+
 }
 
 impl GridBotHelper {
@@ -335,6 +863,35 @@ impl GridBotHelper {
             .json::<Option<Vec<RequestOrder>>>()
     }
 
+    pub async fn query_bot_orders(&self, bot_id: String, from_level: usize, limit: usize) -> Result<Vec<LevelOrdersOutput>, workspaces::error::Error> {
+        log!("start query_bot_orders");
+        self.0
+            .call("query_bot_orders")
+            .args_json(json!({
+                "bot_id": bot_id,
+                "from_level": from_level,
+                "limit": limit,
+            }))
+            .view()
+            .await?
+            .json::<Vec<LevelOrdersOutput>>()
+    }
+
+    pub async fn query_pair_depth(&self, pair_id: String, side: OrderSide, price_from: Option<U128>, limit: usize) -> Result<Vec<DepthOutput>, workspaces::error::Error> {
+        log!("start query_pair_depth");
+        self.0
+            .call("query_pair_depth")
+            .args_json(json!({
+                "pair_id": pair_id,
+                "side": side,
+                "price_from": price_from,
+                "limit": limit,
+            }))
+            .view()
+            .await?
+            .json::<Vec<DepthOutput>>()
+    }
+
     pub async fn query_bot(&self, bot_id: String) -> Result<Option<GridBot>, workspaces::error::Error> {
         log!("start query_bot");
         self.0
@@ -359,6 +916,109 @@ impl GridBotHelper {
             .json::<Option<Vec<GridBot>>>()
     }
 
+    pub async fn query_user_bots(&self, user: &AccountId, from: u64, limit: u64, include_closed: bool) -> Result<Vec<GridBot>, workspaces::error::Error> {
+        log!("start query_user_bots");
+        self.0
+            .call("query_user_bots")
+            .args_json(json!({
+                "user": user,
+                "from": from,
+                "limit": limit,
+                "include_closed": include_closed,
+            }))
+            .view()
+            .await?
+            .json::<Vec<GridBot>>()
+    }
+
+    pub async fn query_user_bots_count(&self, user: &AccountId, include_closed: bool) -> Result<u64, workspaces::error::Error> {
+        log!("start query_user_bots_count");
+        self.0
+            .call("query_user_bots_count")
+            .args_json(json!({
+                "user": user,
+                "include_closed": include_closed,
+            }))
+            .view()
+            .await?
+            .json::<u64>()
+    }
+
+    pub async fn query_pair_bots(&self, pair_id: String, from_bot_id: Option<String>, limit: u64) -> Result<Vec<GridBot>, workspaces::error::Error> {
+        log!("start query_pair_bots");
+        self.0
+            .call("query_pair_bots")
+            .args_json(json!({
+                "pair_id": pair_id,
+                "from_bot_id": from_bot_id,
+                "limit": limit,
+            }))
+            .view()
+            .await?
+            .json::<Vec<GridBot>>()
+    }
+
+    pub async fn query_pairs(&self, from: u64, limit: u64) -> Result<Vec<PairOutput>, workspaces::error::Error> {
+        log!("start query_pairs");
+        self.0
+            .call("query_pairs")
+            .args_json(json!({
+                "from": from,
+                "limit": limit,
+            }))
+            .view()
+            .await?
+            .json::<Vec<PairOutput>>()
+    }
+
+    pub async fn query_token_accounting(&self, token: AccountId) -> Result<TokenAccountingOutput, workspaces::error::Error> {
+        log!("start query_token_accounting");
+        self.0
+            .call("query_token_accounting")
+            .args_json(json!({
+                "token": token,
+            }))
+            .view()
+            .await?
+            .json::<TokenAccountingOutput>()
+    }
+
+    pub async fn query_multi_token(&self, token: &AccountId) -> Result<Option<MultiToken>, workspaces::error::Error> {
+        log!("start query_multi_token");
+        self.0
+            .call("query_multi_token")
+            .args_json(json!({
+                "token": token,
+            }))
+            .view()
+            .await?
+            .json::<Option<MultiToken>>()
+    }
+
+    pub async fn query_pending_withdrawals(&self, user: &AccountId) -> Result<Vec<PendingWithdrawal>, workspaces::error::Error> {
+        log!("start query_pending_withdrawals");
+        self.0
+            .call("query_pending_withdrawals")
+            .args_json(json!({
+                "user": user,
+            }))
+            .view()
+            .await?
+            .json::<Vec<PendingWithdrawal>>()
+    }
+
+    pub async fn query_pair_price(&self, pair_id: String) -> Result<Option<PairPrice>, workspaces::error::Error> {
+        log!("start query_pair_price");
+        self.0
+            .call("query_pair_price")
+            .args_json(json!({
+                "pair_id": pair_id,
+            }))
+            .view()
+            .await?
+            .json::<Option<PairPrice>>()
+    }
+
     pub async fn query_protocol_fee(&self, token: AccountId) -> Result<U128, workspaces::error::Error> {
         log!("start query_protocol_fee");
         self.0
@@ -396,6 +1056,19 @@ impl GridBotHelper {
             .json::<Option<U128>>()
     }
 
+    pub async fn query_user_token_registered(&self, user: &AccountId, token: AccountId) -> Result<bool, workspaces::error::Error> {
+        log!("start query_user_token_registered");
+        self.0
+            .call("query_user_token_registered")
+            .args_json(json!({
+                "user": user,
+                "token": token,
+            }))
+            .view()
+            .await?
+            .json::<bool>()
+    }
+
     pub async fn query_user_locked_balance(&self, user: &AccountId,token: AccountId) -> Result<Option<U128>, workspaces::error::Error> {
         log!("start query_user_locked_balance");
         self.0
@@ -0,0 +1,87 @@
+use near_sdk::AccountId;
+use near_sdk::json_types::{I64, U128, U64};
+use serde_json::json;
+use workspaces::{Account, Contract};
+use workspaces::result::ExecutionFinalResult;
+use crate::*;
+
+/// same as MockMode of mock_pyth
+pub enum MockPythMode {
+    Normal,
+    ReturnNone,
+    Panic,
+}
+
+impl MockPythMode {
+    fn as_str(&self) -> &str {
+        match self {
+            MockPythMode::Normal => "Normal",
+            MockPythMode::ReturnNone => "ReturnNone",
+            MockPythMode::Panic => "Panic",
+        }
+    }
+}
+
+pub struct MockPythHelper(pub Contract);
+
+impl MockPythHelper {
+    pub fn get_account_id(&self) -> AccountId {
+        return AccountId::from_str(self.0.id()).expect("Invalid AccountId");
+    }
+
+    /// publish_time None means current block time
+    pub async fn set_price(&self, caller: &Account, price_identifier: &str, price: i64, conf: u64, expo: i32, publish_time: Option<i64>) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_price");
+        caller
+            .call(self.0.id(), "set_price")
+            .args_json(json!({
+                "price_identifier": price_identifier,
+                "price": I64::from(price),
+                "conf": U64::from(conf),
+                "expo": expo,
+                "publish_time": publish_time,
+            }))
+            .gas(20_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn remove_price(&self, caller: &Account, price_identifier: &str) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start remove_price");
+        caller
+            .call(self.0.id(), "remove_price")
+            .args_json(json!({
+                "price_identifier": price_identifier,
+            }))
+            .gas(20_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    /// priceoracle price, usd value of 1 smallest token unit = multiplier / 10^decimals
+    pub async fn set_asset_price(&self, caller: &Account, asset_id: &AccountId, multiplier: u128, decimals: u8) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_asset_price");
+        caller
+            .call(self.0.id(), "set_asset_price")
+            .args_json(json!({
+                "asset_id": asset_id,
+                "multiplier": U128::from(multiplier),
+                "decimals": decimals,
+            }))
+            .gas(20_000_000_000_000)
+            .transact()
+            .await
+    }
+
+    pub async fn set_mode(&self, caller: &Account, mode: MockPythMode) -> Result<ExecutionFinalResult, workspaces::error::Error> {
+        log!("start set_mode");
+        caller
+            .call(self.0.id(), "set_mode")
+            .args_json(json!({
+                "mode": mode.as_str(),
+            }))
+            .gas(20_000_000_000_000)
+            .transact()
+            .await
+    }
+}
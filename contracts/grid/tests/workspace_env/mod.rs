@@ -3,9 +3,11 @@
 mod setup;
 mod contract_grid;
 mod contract_mock_ft;
+mod contract_mock_pyth;
 mod utils;
 
 pub use setup::*;
 pub use contract_grid::*;
 pub use contract_mock_ft::*;
+pub use contract_mock_pyth::*;
 pub use utils::*;
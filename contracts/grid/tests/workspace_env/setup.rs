@@ -10,6 +10,7 @@ use near_sdk::{AccountId};
 
 pub const GRID_WASM: &str = "res/grid.wasm";
 pub const TOKEN_WASM: &str = "res/token.wasm";
+pub const MOCK_PYTH_WASM: &str = "res/mock_pyth.wasm";
 
 pub async fn deploy_grid_bot(
     worker: &Worker<Testnet>,
@@ -68,3 +69,20 @@ pub async fn deploy_token(
 
     Ok(FtContractHelper(contract))
 }
+
+pub async fn deploy_mock_pyth(
+    worker: &Worker<Testnet>,
+    owner: &Account,
+) -> Result<MockPythHelper, workspaces::error::Error> {
+    let contract = worker.dev_deploy(&std::fs::read(MOCK_PYTH_WASM).unwrap()).await?;
+    println!("mock pyth deployed: {:?}", contract.id().clone());
+
+    contract
+        .call("new")
+        .args_json(serde_json::json!({ "owner_id": owner.id() }))
+        .max_gas()
+        .transact()
+        .await?;
+
+    Ok(MockPythHelper(contract))
+}
@@ -118,6 +118,22 @@ pub fn check_success(result: Result<ExecutionFinalResult, workspaces::error::Err
     }
 }
 
+/// the execution failed with error message
+pub fn check_failure(result: Result<ExecutionFinalResult, workspaces::error::Error>, error: &str) {
+    match result {
+        Ok(execution_result) => {
+            if !execution_result.is_failure() {
+                println!("success:{:?}", execution_result);
+            }
+            assert!(execution_result.is_failure());
+            assert!(format!("{:?}", execution_result).contains(error));
+        },
+        Err(error) => {
+            println!("directly error:{:?}", error);
+        }
+    }
+}
+
 pub fn get_time_stamp() -> u64 {
     let start = SystemTime::now();
     let timestamp;
@@ -0,0 +1,8 @@
+#!/bin/sh
+
+echo ">> Building MockPythContract"
+
+rustup target add wasm32-unknown-unknown
+cargo build --all --target wasm32-unknown-unknown --release
+rm ../grid/res/mock_pyth.wasm
+cp target/wasm32-unknown-unknown/release/mock_pyth.wasm ../grid/res/mock_pyth.wasm
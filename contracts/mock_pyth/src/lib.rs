@@ -0,0 +1,147 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::{I64, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
+
+/// Same json format as pyth, a 64 character hex string
+pub type PriceIdentifier = String;
+
+/// Same as pyth Price, `x * (10^expo)`
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Price {
+    pub price:        I64,
+    /// Confidence interval around the price
+    pub conf:         U64,
+    /// The exponent
+    pub expo:         i32,
+    /// Unix timestamp of when this price was computed
+    pub publish_time: i64,
+}
+
+/// Same as priceoracle AssetPrice, usd value of 1 smallest token unit = multiplier / 10^decimals
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetPrice {
+    pub multiplier: U128,
+    pub decimals: u8,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetOptionalPrice {
+    pub asset_id: AccountId,
+    pub price: Option<AssetPrice>,
+}
+
+/// Same as priceoracle PriceData
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceData {
+    /// nanoseconds
+    pub timestamp: U64,
+    pub recency_duration_sec: u32,
+    pub prices: Vec<AssetOptionalPrice>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MockMode {
+    /// return the price set by owner
+    Normal,
+    /// return None for every identifier and asset
+    ReturnNone,
+    /// panic for every identifier and asset
+    Panic,
+}
+
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault)]
+pub struct MockPythContract {
+    owner_id: AccountId,
+    mode: MockMode,
+    /// price_map[price_identifier] = price
+    price_map: LookupMap<PriceIdentifier, Price>,
+    /// asset_price_map[asset_id] = priceoracle price
+    asset_price_map: LookupMap<AccountId, AssetPrice>,
+}
+
+#[near_bindgen]
+impl MockPythContract {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        Self {
+            owner_id,
+            mode: MockMode::Normal,
+            price_map: LookupMap::new(b"p".to_vec()),
+            asset_price_map: LookupMap::new(b"a".to_vec()),
+        }
+    }
+
+    /// publish_time is current block time if not set, so price is always fresh
+    pub fn set_price(&mut self, price_identifier: PriceIdentifier, price: I64, conf: U64, expo: i32, publish_time: Option<i64>) {
+        self.assert_owner();
+        let price = Price {
+            price,
+            conf,
+            expo,
+            publish_time: publish_time.unwrap_or((env::block_timestamp_ms() / 1000) as i64),
+        };
+        self.price_map.insert(&price_identifier.to_lowercase(), &price);
+    }
+
+    pub fn remove_price(&mut self, price_identifier: PriceIdentifier) {
+        self.assert_owner();
+        self.price_map.remove(&price_identifier.to_lowercase());
+    }
+
+    pub fn set_asset_price(&mut self, asset_id: AccountId, multiplier: U128, decimals: u8) {
+        self.assert_owner();
+        self.asset_price_map.insert(&asset_id, &AssetPrice { multiplier, decimals });
+    }
+
+    pub fn remove_asset_price(&mut self, asset_id: AccountId) {
+        self.assert_owner();
+        self.asset_price_map.remove(&asset_id);
+    }
+
+    pub fn set_mode(&mut self, mode: MockMode) {
+        self.assert_owner();
+        self.mode = mode;
+    }
+
+    pub fn get_mode(&self) -> MockMode {
+        self.mode.clone()
+    }
+
+    pub fn get_price(&self, price_identifier: PriceIdentifier) -> Option<Price> {
+        match self.mode {
+            MockMode::Normal => self.price_map.get(&price_identifier.to_lowercase()),
+            MockMode::ReturnNone => None,
+            MockMode::Panic => env::panic_str("MOCK_ORACLE_PANIC"),
+        }
+    }
+
+    /// priceoracle view, timestamp is current block time
+    pub fn get_price_data(&self, asset_ids: Option<Vec<AccountId>>) -> PriceData {
+        if self.mode == MockMode::Panic {
+            env::panic_str("MOCK_ORACLE_PANIC");
+        }
+        let prices = asset_ids.unwrap_or_default().into_iter().map(|asset_id| {
+            let price = if self.mode == MockMode::Normal { self.asset_price_map.get(&asset_id) } else { None };
+            AssetOptionalPrice { asset_id, price }
+        }).collect();
+        PriceData {
+            timestamp: U64::from(env::block_timestamp()),
+            recency_duration_sec: 90,
+            prices,
+        }
+    }
+}
+
+impl MockPythContract {
+    fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "ERR_NOT_ALLOWED");
+    }
+}
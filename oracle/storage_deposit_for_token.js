@@ -0,0 +1,43 @@
+const nearAPI = require("near-api-js");
+const path = require("path");
+const os = require("os");
+const Big = require("big.js");
+
+const { getConfig } = require("./config");
+
+// register the grid contract on a token, paid by owner
+// note: this owner method was storage_deposit(token, storage_fee) before NEP-145 storage management,
+// storage_deposit now registers the caller's own storage account and ignores token/storage_fee,
+// so owner calls must use storage_deposit_for_token
+// usage: NEAR_ACCOUNT_ID=owner GRID_CONTRACT_ID=grid node storage_deposit_for_token.js <token> [storage_fee_in_near]
+async function storageDepositForToken() {
+    const token = process.argv[2];
+    const storageFee = Big(10).pow(24).mul(process.argv[3] || "0.1").toFixed(0);
+    const NearConfig = getConfig("development");
+
+    const keyStore = new nearAPI.keyStores.InMemoryKeyStore();
+    const keyPath = path.join(os.homedir(), ".near-credentials", NearConfig.networkId, NearConfig.accountId + ".json");
+    const near = await nearAPI.connect(
+        Object.assign({keyPath, deps: {keyStore}}, NearConfig)
+    );
+    const account = new nearAPI.Account(near.connection, NearConfig.accountId);
+    const contract = new nearAPI.Contract(
+        account,
+        process.env.GRID_CONTRACT_ID,
+        {
+            viewMethods: [],
+            changeMethods: ["storage_deposit_for_token"],
+        }
+    );
+    const result = await contract.storage_deposit_for_token(
+        {
+            token,
+            storage_fee: storageFee,
+        },
+        Big(10).pow(12).mul(100).toFixed(0),
+        storageFee
+    )
+    console.log("Storage Deposit For Token Result: ", result);
+}
+
+storageDepositForToken()